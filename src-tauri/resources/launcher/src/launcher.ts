@@ -84,13 +84,15 @@ function handleRequest(req: IncomingMessage, res: ServerResponse): void {
     return;
   }
 
-  // GET /health - check if the child is still alive
+  // GET /health - check if the child is still alive. Also reports both
+  // PIDs so the Rust backend can record them as processes it owns.
   if (req.method === "GET" && req.url === "/health") {
     res.writeHead(200, { "Content-Type": "application/json" });
     res.end(
       JSON.stringify({
         ok: true,
         pid: child.pid,
+        launcherPid: process.pid,
         childAlive: !child.killed && child.exitCode === null,
       }),
     );
@@ -0,0 +1,270 @@
+//! OpenCode agents.
+//!
+//! The generated `opencode.json` always contains BloxBot's built-in agents
+//! (see `opencode_config`), including the read-only `inspect` agent built
+//! here. On top of those, users can define their own in
+//! `AppConfig::custom_agents` — e.g. a "UI designer" or "code reviewer"
+//! agent shared across a team. This module turns them into OpenCode's agent
+//! format and provides the CRUD commands; every change is applied to the
+//! running server through `opencode::reconfigure`.
+
+use std::collections::BTreeMap;
+
+use tauri::AppHandle;
+
+use crate::config::{AgentMode, CustomAgent, ToolAction};
+use crate::opencode::SharedOpenCodeState;
+use crate::opencode_config::{AgentEntry, PermissionRule};
+
+/// Names of agents generated by BloxBot or built into OpenCode. Custom
+/// agents may not reuse them.
+pub const BUILTIN_AGENTS: &[&str] = &[
+    "build",
+    "plan",
+    "general",
+    "explore",
+    "studio",
+    READ_ONLY_AGENT,
+];
+
+pub fn is_builtin(name: &str) -> bool {
+    BUILTIN_AGENTS.iter().any(|b| b.eq_ignore_ascii_case(name))
+}
+
+// ── Read-only agent ─────────────────────────────────────────────────────
+
+/// Name of the built-in agent that may inspect the place but not change it.
+pub const READ_ONLY_AGENT: &str = "inspect";
+
+/// OpenCode's own tools that write files or run commands.
+const MUTATING_BUILTIN_TOOLS: &[&str] = &["edit", "write", "patch", "bash"];
+
+const READ_ONLY_PROMPT: &str = concat!(
+    "You are BloxBot in inspection mode. Explore and explain the open Roblox Studio place ",
+    "using the read-only roblox-studio tools: project structure, instances, properties, ",
+    "attributes, tags, script sources and playtest output. ",
+    "You cannot create, modify or delete anything, run code, or start playtests. ",
+    "If the user asks for a change, describe exactly what should change and suggest ",
+    "switching to the studio agent to apply it.",
+);
+
+/// Tools the read-only agent is denied, as OpenCode tool IDs or globs:
+/// every roblox-studio tool (including ones a newer MCP server adds that
+/// the catalogue doesn't know yet) plus OpenCode's file-editing and shell
+/// tools. The catalogue's read-only tools are re-enabled on top.
+pub fn read_only_deny_list() -> Vec<String> {
+    std::iter::once(crate::studio_tools::opencode_tool_id("*"))
+        .chain(MUTATING_BUILTIN_TOOLS.iter().map(|t| t.to_string()))
+        .collect()
+}
+
+/// OpenCode's config for the read-only agent. The deny list is enforced
+/// twice: the tools are disabled so the model never sees them, and the
+/// permission rules reject them should they be re-enabled elsewhere.
+/// Read-only tools keep the action `policy` gives them, and are allowed
+/// otherwise.
+pub fn read_only_agent(policy: &BTreeMap<String, ToolAction>) -> AgentEntry {
+    let deny = read_only_deny_list();
+    let mut tools: BTreeMap<String, bool> = deny.iter().map(|t| (t.clone(), false)).collect();
+    let mut permission: BTreeMap<String, PermissionRule> = deny
+        .into_iter()
+        .map(|t| (t, ToolAction::Deny.into()))
+        .collect();
+    for tool in crate::studio_tools::read_only() {
+        let id = crate::studio_tools::opencode_tool_id(tool);
+        let action =
+            crate::studio_tools::resolve(policy, tool).map_or(ToolAction::Allow, |(_, a)| a);
+        tools.insert(id.clone(), true);
+        permission.insert(id, action.into());
+    }
+    AgentEntry {
+        mode: Some(AgentMode::Primary),
+        description: Some("Inspects the place without changing anything".to_string()),
+        prompt: Some(READ_ONLY_PROMPT.to_string()),
+        tools,
+        permission,
+        ..Default::default()
+    }
+}
+
+// ── OpenCode format ─────────────────────────────────────────────────────
+
+/// OpenCode's config for a custom agent.
+fn to_opencode(agent: &CustomAgent) -> AgentEntry {
+    let mut tools = BTreeMap::new();
+    if let Some(allowed) = &agent.allowed_tools {
+        // Deny everything, then re-enable the listed tools. OpenCode
+        // applies the most specific matching pattern.
+        tools.insert("*".to_string(), false);
+        for tool in allowed {
+            tools.insert(tool.clone(), true);
+        }
+    }
+    AgentEntry {
+        mode: Some(agent.mode),
+        description: agent.description.clone(),
+        prompt: Some(agent.prompt.clone()),
+        model: agent.model.clone(),
+        temperature: agent.temperature,
+        tools,
+        ..Default::default()
+    }
+}
+
+/// Add `agents` to the generated `agent` map. Agents whose name is
+/// already taken are skipped; validation normally prevents that.
+pub fn merge_custom(agent_map: &mut BTreeMap<String, AgentEntry>, agents: &[CustomAgent]) {
+    for agent in agents {
+        if agent_map.contains_key(&agent.name) || is_builtin(&agent.name) {
+            log::warn!(
+                "Custom agent \"{}\" collides with an existing agent; skipping it",
+                agent.name
+            );
+            continue;
+        }
+        agent_map.insert(agent.name.clone(), to_opencode(agent));
+    }
+}
+
+// ── Apply ───────────────────────────────────────────────────────────────
+
+async fn apply(state: &SharedOpenCodeState, app: &AppHandle) {
+    match crate::opencode::reconfigure(state, app).await {
+        Ok(outcome) => log::info!("Custom agents applied ({outcome:?})"),
+        Err(e) => log::warn!("Custom agents saved but not applied yet: {e}"),
+    }
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn list_custom_agents() -> Vec<CustomAgent> {
+    crate::config::get().custom_agents
+}
+
+/// Add a new custom agent. Fails if the name is taken.
+#[tauri::command]
+pub async fn create_custom_agent(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+    agent: CustomAgent,
+) -> Result<Vec<CustomAgent>, String> {
+    let cfg = crate::config::update(&app, |cfg| {
+        cfg.custom_agents.push(agent);
+        Ok(())
+    })?;
+    apply(state.inner(), &app).await;
+    Ok(cfg.custom_agents)
+}
+
+/// Replace the custom agent called `name`. `agent.name` may differ to
+/// rename it.
+#[tauri::command]
+pub async fn update_custom_agent(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+    name: String,
+    agent: CustomAgent,
+) -> Result<Vec<CustomAgent>, String> {
+    let cfg = crate::config::update(&app, |cfg| {
+        let slot = cfg
+            .custom_agents
+            .iter_mut()
+            .find(|a| a.name == name)
+            .ok_or_else(|| format!("No custom agent named \"{name}\""))?;
+        *slot = agent;
+        Ok(())
+    })?;
+    apply(state.inner(), &app).await;
+    Ok(cfg.custom_agents)
+}
+
+#[tauri::command]
+pub async fn delete_custom_agent(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+    name: String,
+) -> Result<Vec<CustomAgent>, String> {
+    let cfg = crate::config::update(&app, |cfg| {
+        let before = cfg.custom_agents.len();
+        cfg.custom_agents.retain(|a| a.name != name);
+        if cfg.custom_agents.len() == before {
+            return Err(format!("No custom agent named \"{name}\""));
+        }
+        Ok(())
+    })?;
+    apply(state.inner(), &app).await;
+    Ok(cfg.custom_agents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The value OpenCode applies to `id`: an exact key, else the matching
+    /// `prefix*` glob.
+    fn effective<V: Clone>(map: &BTreeMap<String, V>, id: &str) -> Option<V> {
+        map.get(id).cloned().or_else(|| {
+            map.iter()
+                .find(|(key, _)| {
+                    key.strip_suffix('*')
+                        .is_some_and(|prefix| id.starts_with(prefix))
+                })
+                .map(|(_, v)| v.clone())
+        })
+    }
+
+    const DENY: PermissionRule = PermissionRule::Action(ToolAction::Deny);
+
+    #[test]
+    fn read_only_agent_denies_mutating_studio_tools() {
+        let agent = read_only_agent(&BTreeMap::new());
+        for tool in [
+            "set_script_source",
+            "edit_script_lines",
+            "mass_set_property",
+            "create_object_with_properties",
+            "execute_luau",
+            "start_playtest",
+        ] {
+            let id = format!("roblox-studio_{tool}");
+            assert_eq!(effective(&agent.tools, &id), Some(false), "{id}");
+            assert_eq!(effective(&agent.permission, &id), Some(DENY), "{id}");
+        }
+    }
+
+    #[test]
+    fn read_only_agent_denies_unknown_studio_tools() {
+        let agent = read_only_agent(&BTreeMap::new());
+        let id = "roblox-studio_some_future_tool";
+        assert_eq!(effective(&agent.tools, id), Some(false));
+        assert_eq!(effective(&agent.permission, id), Some(DENY));
+    }
+
+    #[test]
+    fn read_only_agent_denies_builtin_editing_tools() {
+        let agent = read_only_agent(&BTreeMap::new());
+        for tool in MUTATING_BUILTIN_TOOLS {
+            assert_eq!(agent.tools.get(*tool), Some(&false), "{tool}");
+            assert_eq!(agent.permission.get(*tool), Some(&DENY), "{tool}");
+        }
+    }
+
+    #[test]
+    fn read_only_agent_keeps_read_tools_under_the_policy() {
+        let policy = BTreeMap::from([("get_script_source".to_string(), ToolAction::Ask)]);
+        let agent = read_only_agent(&policy);
+        assert_eq!(
+            effective(&agent.tools, "roblox-studio_get_script_source"),
+            Some(true)
+        );
+        assert_eq!(
+            effective(&agent.permission, "roblox-studio_get_script_source"),
+            Some(ToolAction::Ask.into())
+        );
+        assert_eq!(
+            effective(&agent.permission, "roblox-studio_get_place_info"),
+            Some(ToolAction::Allow.into())
+        );
+    }
+}
@@ -0,0 +1,599 @@
+//! Per-turn workspace checkpoints.
+//!
+//! Before each agent turn the frontend calls `create_checkpoint`, which
+//! snapshots the workspace into a hidden git object store in
+//! `.opencode/state/checkpoints` and records the snapshot as
+//! `refs/checkpoints/<session>/<message>`. The message ID is chosen here
+//! and passed on to OpenCode with the prompt (it accepts client-chosen
+//! IDs), so the checkpoint is taken strictly before the turn yet still
+//! keyed by the message that starts it.
+//!
+//! The store is a bare repository whose work tree is the workspace, so
+//! git's content addressing keeps repeated snapshots cheap and the
+//! workspace's own `.gitignore` files apply. It is independent of any git
+//! repository the user keeps in the workspace. `.opencode/` is never
+//! snapshotted.
+//!
+//! Restoring first saves the current files under `refs/backups/<time>`,
+//! so a restore can itself be undone with plain git if needed.
+//!
+//! Only the newest `KEEP_PER_SESSION` checkpoints of a session and
+//! `KEEP_BACKUPS` backups are kept, and a deleted session's checkpoints go
+//! with it (`delete_checkpoints`). Snapshots nothing refers to any more are
+//! left to `git gc --auto`.
+//!
+//! Requires a `git` executable on `PATH`, like OpenCode's own snapshots.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+/// Bare repository inside `.opencode/state`.
+const STORE_DIR: &str = "checkpoints";
+
+const CHECKPOINT_REFS: &str = "refs/checkpoints";
+const BACKUP_REFS: &str = "refs/backups";
+
+/// Checkpoints kept per session; taking one more drops the oldest.
+const KEEP_PER_SESSION: usize = 100;
+
+/// Restore backups kept.
+const KEEP_BACKUPS: usize = 20;
+
+// ── Types ───────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    pub session_id: String,
+    pub message_id: String,
+    /// Commit holding the snapshot.
+    pub commit: String,
+    /// Seconds since the UNIX epoch.
+    pub created_at: u64,
+}
+
+/// A file that differs between a checkpoint and the workspace.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedFile {
+    pub path: String,
+    /// `added`, `modified` or `deleted`, relative to the checkpoint.
+    pub status: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointDiff {
+    pub files: Vec<ChangedFile>,
+    /// Unified diff from the checkpoint to the current files.
+    pub patch: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    /// Files written back from the checkpoint.
+    pub restored: Vec<String>,
+    /// Files created after the checkpoint, now deleted.
+    pub removed: Vec<String>,
+    /// Files created after the checkpoint that could not be deleted, with
+    /// the reason. Everything else is still restored.
+    pub kept: Vec<String>,
+    /// Commit with the files as they were before the restore.
+    pub backup: String,
+}
+
+// ── Message IDs ─────────────────────────────────────────────────────────
+
+const BASE62: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Last timestamp and counter handed out, so IDs from the same millisecond
+/// still ascend.
+static LAST_ID: Mutex<(u64, u64)> = Mutex::new((0, 0));
+
+/// A new message ID in OpenCode's format: `msg_`, 12 hex digits of
+/// `millis * 0x1000 + counter` (truncated to 48 bits), then 14 random
+/// base62 characters. OpenCode orders messages by ID, so this must ascend
+/// the same way its own IDs do.
+///
+/// The format is OpenCode's internal `Identifier.ascending` (`src/id/id.ts`),
+/// not a documented API. Check it again whenever the bundled OpenCode is
+/// upgraded: IDs that sort wrong would misorder the session's messages.
+fn new_message_id() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let counter = {
+        let mut last = LAST_ID.lock().unwrap_or_else(|e| e.into_inner());
+        if last.0 != now {
+            *last = (now, 0);
+        }
+        last.1 += 1;
+        last.1
+    };
+    let time = now.wrapping_mul(0x1000).wrapping_add(counter) & 0xffff_ffff_ffff;
+
+    let mut id = format!("msg_{time:012x}");
+    let mut random = 0u64;
+    for i in 0..14 {
+        if i % 8 == 0 {
+            random = RandomState::new().build_hasher().finish();
+        }
+        id.push(BASE62[(random % 62) as usize] as char);
+        random /= 62;
+    }
+    id
+}
+
+/// Session and message IDs become ref names, so only allow what OpenCode
+/// generates.
+fn validate_id(kind: &str, id: &str) -> Result<(), String> {
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!("Invalid {kind} ID \"{id}\""));
+    }
+    Ok(())
+}
+
+// ── Store ───────────────────────────────────────────────────────────────
+
+struct Store {
+    git_dir: PathBuf,
+    work_tree: PathBuf,
+}
+
+impl Store {
+    /// Open the store for `workspace`, creating it on first use.
+    fn open(workspace: &Path) -> Result<Self, String> {
+        let store = Self {
+            git_dir: workspace.join(".opencode").join("state").join(STORE_DIR),
+            work_tree: workspace.to_path_buf(),
+        };
+        if !store.git_dir.join("HEAD").exists() {
+            std::fs::create_dir_all(&store.git_dir)
+                .map_err(|e| format!("Failed to create {}: {e}", store.git_dir.display()))?;
+            // `init --bare` refuses a work tree, so this one runs without.
+            let mut init = Command::new("git");
+            init.args(["init", "--bare", "--quiet"]).arg(&store.git_dir);
+            run_git(init, &["init"])?;
+            log::info!("Created checkpoint store at {}", store.git_dir.display());
+        }
+        store.write_excludes()?;
+        Ok(store)
+    }
+
+    /// Keep OpenCode's own data out of snapshots.
+    fn write_excludes(&self) -> Result<(), String> {
+        let info = self.git_dir.join("info");
+        std::fs::create_dir_all(&info)
+            .map_err(|e| format!("Failed to create {}: {e}", info.display()))?;
+        std::fs::write(info.join("exclude"), "/.opencode/\n")
+            .map_err(|e| format!("Failed to write checkpoint excludes: {e}"))
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, String> {
+        let mut cmd = Command::new("git");
+        cmd.arg("--git-dir")
+            .arg(&self.git_dir)
+            .arg("--work-tree")
+            .arg(&self.work_tree)
+            .args([
+                "-c",
+                "core.autocrlf=false",
+                "-c",
+                "core.quotepath=false",
+                "-c",
+                "user.name=BloxBot",
+                "-c",
+                "user.email=checkpoints@bloxbot.local",
+            ])
+            .args(args)
+            .current_dir(&self.work_tree);
+        run_git(cmd, args)
+    }
+
+    /// Stage the current files in the store's index and commit them.
+    fn snapshot(&self, message: &str) -> Result<String, String> {
+        self.git(&["add", "--all", "--", "."])?;
+        let tree = self.git(&["write-tree"])?;
+        Ok(self
+            .git(&["commit-tree", tree.trim(), "-m", message])?
+            .trim()
+            .to_string())
+    }
+
+    /// Delete all but the newest `keep` refs under `prefix`. Ref names
+    /// ascend with time (message IDs, millisecond stamps), so sorting by
+    /// name is enough. Returns how many were deleted.
+    fn prune_refs(&self, prefix: &str, keep: usize) -> Result<usize, String> {
+        let out = self.git(&[
+            "for-each-ref",
+            "--sort=-refname",
+            "--format=%(refname)",
+            prefix,
+        ])?;
+        let stale: Vec<&str> = out.lines().skip(keep).collect();
+        for refname in &stale {
+            self.git(&["update-ref", "-d", refname])?;
+        }
+        Ok(stale.len())
+    }
+
+    /// Let git pack the store and drop unreferenced snapshots once enough
+    /// have piled up. Failing that is not worth failing the caller.
+    fn collect_garbage(&self) {
+        if let Err(e) = self.git(&["-c", "gc.autoDetach=false", "gc", "--auto", "--quiet"]) {
+            log::warn!("Checkpoint store cleanup failed: {e}");
+        }
+    }
+
+    fn resolve(&self, session_id: &str, message_id: &str) -> Result<String, String> {
+        validate_id("session", session_id)?;
+        validate_id("message", message_id)?;
+        let refname = format!("{CHECKPOINT_REFS}/{session_id}/{message_id}");
+        self.git(&["rev-parse", "--verify", "--quiet", &refname])
+            .map(|commit| commit.trim().to_string())
+            .map_err(|_| format!("No checkpoint for message {message_id}"))
+    }
+
+    /// Files that differ between `commit` and the store's index, as
+    /// `(status letter, path)`. Call after `snapshot` so the index matches
+    /// the workspace.
+    fn changed_files(&self, commit: &str) -> Result<Vec<(char, String)>, String> {
+        let out = self.git(&[
+            "diff",
+            "--cached",
+            "--name-status",
+            "--no-renames",
+            "-z",
+            commit,
+        ])?;
+        let mut fields = out.split('\0').filter(|f| !f.is_empty());
+        let mut changes = Vec::new();
+        while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+            changes.push((status.chars().next().unwrap_or('M'), path.to_string()));
+        }
+        Ok(changes)
+    }
+
+    /// Put the work tree back to `commit`, after saving the current files
+    /// as a backup.
+    fn restore(&self, commit: &str, label: &str) -> Result<RestoreReport, String> {
+        let backup = self.snapshot(&format!("before restoring {label}"))?;
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        self.git(&["update-ref", &format!("{BACKUP_REFS}/{stamp}"), &backup])?;
+        self.prune_refs(&format!("{BACKUP_REFS}/"), KEEP_BACKUPS)?;
+
+        let mut report = RestoreReport {
+            restored: Vec::new(),
+            removed: Vec::new(),
+            kept: Vec::new(),
+            backup,
+        };
+        for (status, path) in self.changed_files(commit)? {
+            if status != 'A' {
+                report.restored.push(path);
+                continue;
+            }
+            // A nested repository shows up as a single gitlink entry; its
+            // files are not ours to delete.
+            let file = self.work_tree.join(&path);
+            if file.is_dir() {
+                report.kept.push(format!("{path}: nested repository"));
+                continue;
+            }
+            match std::fs::remove_file(&file) {
+                Ok(()) => report.removed.push(path),
+                Err(e) => report.kept.push(format!("{path}: {e}")),
+            }
+        }
+        self.git(&["read-tree", commit])?;
+        self.git(&["checkout-index", "--all", "--force"])?;
+        Ok(report)
+    }
+}
+
+/// Run `cmd` and return its stdout. `args` name the subcommand in errors.
+fn run_git(mut cmd: Command, args: &[&str]) -> Result<String, String> {
+    let out = cmd
+        .output()
+        .map_err(|e| format!("Failed to run git (is it installed?): {e}"))?;
+    if !out.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.iter()
+                .find(|a| !a.starts_with('-') && !a.contains('='))
+                .unwrap_or(&""),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// One lock per workspace store. Snapshots, diffs and restores all stage
+/// into the store's single index, so two of them at once would mix up each
+/// other's trees.
+static STORE_LOCKS: Mutex<Option<HashMap<PathBuf, Arc<Mutex<()>>>>> = Mutex::new(None);
+
+/// Run `f` on the current workspace's store while holding its lock.
+fn with_store<T>(f: impl FnOnce(&Store) -> Result<T, String>) -> Result<T, String> {
+    let workspace = crate::paths::workspace_dir()?;
+    let lock = STORE_LOCKS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .entry(workspace.clone())
+        .or_default()
+        .clone();
+    let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+    f(&Store::open(&workspace)?)
+}
+
+/// Run blocking git work off the async runtime.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Checkpoint task failed: {e}"))?
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+/// Snapshot the workspace before a turn of `session_id`. The returned
+/// `message_id` must be sent as the prompt's `messageID`.
+#[tauri::command]
+pub async fn create_checkpoint(session_id: String) -> Result<Checkpoint, String> {
+    validate_id("session", &session_id)?;
+    blocking(move || {
+        with_store(|store| {
+            let message_id = new_message_id();
+            let commit = store.snapshot(&format!("{session_id} {message_id}"))?;
+            let refname = format!("{CHECKPOINT_REFS}/{session_id}/{message_id}");
+            store.git(&["update-ref", &refname, &commit])?;
+            log::info!(
+                "Checkpoint {} for {session_id}/{message_id}",
+                &commit[..12.min(commit.len())]
+            );
+            let pruned = store.prune_refs(
+                &format!("{CHECKPOINT_REFS}/{session_id}/"),
+                KEEP_PER_SESSION,
+            )?;
+            if pruned > 0 {
+                log::debug!("Dropped {pruned} old checkpoint(s) of {session_id}");
+                store.collect_garbage();
+            }
+            Ok(Checkpoint {
+                session_id,
+                message_id,
+                commit,
+                created_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+            })
+        })
+    })
+    .await
+}
+
+/// Checkpoints of `session_id` (or of every session), newest first.
+#[tauri::command]
+pub async fn list_checkpoints(session_id: Option<String>) -> Result<Vec<Checkpoint>, String> {
+    if let Some(id) = &session_id {
+        validate_id("session", id)?;
+    }
+    blocking(move || {
+        with_store(|store| {
+            let prefix = match &session_id {
+                Some(id) => format!("{CHECKPOINT_REFS}/{id}/"),
+                None => format!("{CHECKPOINT_REFS}/"),
+            };
+            let out = store.git(&[
+                "for-each-ref",
+                "--sort=-creatordate",
+                "--format=%(refname)%09%(objectname)%09%(creatordate:unix)",
+                &prefix,
+            ])?;
+            Ok(out
+                .lines()
+                .filter_map(|line| {
+                    let mut cols = line.split('\t');
+                    let refname = cols.next()?;
+                    let commit = cols.next()?.to_string();
+                    let created_at = cols.next()?.parse().ok()?;
+                    let (session_id, message_id) = refname
+                        .strip_prefix(CHECKPOINT_REFS)?
+                        .trim_start_matches('/')
+                        .split_once('/')?;
+                    Some(Checkpoint {
+                        session_id: session_id.to_string(),
+                        message_id: message_id.to_string(),
+                        commit,
+                        created_at,
+                    })
+                })
+                .collect())
+        })
+    })
+    .await
+}
+
+/// What changed in the workspace since the checkpoint.
+#[tauri::command]
+pub async fn diff_checkpoint(
+    session_id: String,
+    message_id: String,
+) -> Result<CheckpointDiff, String> {
+    blocking(move || {
+        with_store(|store| {
+            let commit = store.resolve(&session_id, &message_id)?;
+            store.git(&["add", "--all", "--", "."])?;
+            let files = store
+                .changed_files(&commit)?
+                .into_iter()
+                .map(|(status, path)| ChangedFile {
+                    path,
+                    status: match status {
+                        'A' => "added",
+                        'D' => "deleted",
+                        _ => "modified",
+                    },
+                })
+                .collect();
+            let patch = store.git(&["diff", "--cached", "--no-renames", &commit])?;
+            Ok(CheckpointDiff { files, patch })
+        })
+    })
+    .await
+}
+
+/// Put the workspace back the way it was when the checkpoint was taken.
+/// Files created since then are deleted.
+#[tauri::command]
+pub async fn restore_checkpoint(
+    session_id: String,
+    message_id: String,
+) -> Result<RestoreReport, String> {
+    blocking(move || {
+        with_store(|store| {
+        let commit = store.resolve(&session_id, &message_id)?;
+        let report = store.restore(&commit, &format!("{session_id} {message_id}"))?;
+        for kept in &report.kept {
+            log::warn!("Restore left {kept} in place");
+        }
+        log::info!(
+            "Restored checkpoint {session_id}/{message_id}: {} file(s) restored, {} removed (backup {})",
+            report.restored.len(),
+            report.removed.len(),
+            report.backup
+        );
+        Ok(report)
+        })
+    })
+    .await
+}
+
+/// Drop the checkpoints of a deleted session.
+#[tauri::command]
+pub async fn delete_checkpoints(session_id: String) -> Result<(), String> {
+    validate_id("session", &session_id)?;
+    blocking(move || {
+        with_store(|store| {
+            let deleted = store.prune_refs(&format!("{CHECKPOINT_REFS}/{session_id}/"), 0)?;
+            if deleted > 0 {
+                log::info!("Dropped {deleted} checkpoint(s) of deleted session {session_id}");
+                store.collect_garbage();
+            }
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// A store for a temp workspace that lives as long as the returned guard.
+    fn temp_store() -> (TempDir, Store) {
+        let dir = TempDir::new("checkpoints");
+        let store = Store::open(dir.path()).unwrap();
+        (dir, store)
+    }
+
+    fn checkpoint(store: &Store, session_id: &str) -> String {
+        let message_id = new_message_id();
+        let commit = store.snapshot(&message_id).unwrap();
+        let refname = format!("{CHECKPOINT_REFS}/{session_id}/{message_id}");
+        store.git(&["update-ref", &refname, &commit]).unwrap();
+        message_id
+    }
+
+    #[test]
+    fn message_ids_ascend() {
+        let ids: Vec<String> = (0..100).map(|_| new_message_id()).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(ids
+            .iter()
+            .all(|id| id.len() == 30 && id.starts_with("msg_")));
+    }
+
+    #[test]
+    fn prune_refs_keeps_the_newest() {
+        let (_dir, store) = temp_store();
+        let ids: Vec<String> = (0..4).map(|_| checkpoint(&store, "ses_a")).collect();
+        checkpoint(&store, "ses_b");
+
+        assert_eq!(
+            store
+                .prune_refs(&format!("{CHECKPOINT_REFS}/ses_a/"), 2)
+                .unwrap(),
+            2
+        );
+        assert!(store.resolve("ses_a", &ids[1]).is_err());
+        assert!(store.resolve("ses_a", &ids[2]).is_ok());
+        assert!(store.resolve("ses_a", &ids[3]).is_ok());
+
+        assert_eq!(
+            store
+                .prune_refs(&format!("{CHECKPOINT_REFS}/ses_a/"), 0)
+                .unwrap(),
+            2
+        );
+        let left = store
+            .git(&["for-each-ref", "--format=%(refname)", CHECKPOINT_REFS])
+            .unwrap();
+        assert_eq!(left.lines().count(), 1, "{left}");
+    }
+
+    #[test]
+    fn restore_keeps_nested_repositories_and_restores_the_rest() {
+        let (_dir, store) = temp_store();
+        let file = store.work_tree.join("init.server.luau");
+        std::fs::write(&file, "print(1)").unwrap();
+        let message_id = checkpoint(&store, "ses_a");
+
+        std::fs::write(&file, "print(2)").unwrap();
+        std::fs::write(store.work_tree.join("new.luau"), "return {}").unwrap();
+        let nested = store.work_tree.join("Packages");
+        std::fs::create_dir_all(&nested).unwrap();
+        let git = |args: &[&str]| {
+            let ok = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(&nested)
+                .output()
+                .unwrap()
+                .status
+                .success();
+            assert!(ok, "git {args:?}");
+        };
+        git(&["init", "--quiet"]);
+        std::fs::write(nested.join("wally.lock"), "").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "init"]);
+
+        let commit = store.resolve("ses_a", &message_id).unwrap();
+        let report = store.restore(&commit, "test").unwrap();
+        assert_eq!(report.removed, ["new.luau"]);
+        assert_eq!(report.kept, ["Packages: nested repository"]);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "print(1)");
+        assert!(nested.join("wally.lock").exists());
+    }
+}
@@ -11,9 +11,11 @@
 //! the Tauri store plugin (frontend-only) and Rust constants, making it
 //! impossible for the backend to read user preferences at startup.
 
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Mutex;
 use std::sync::OnceLock;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
@@ -34,6 +36,618 @@ pub struct AppConfig {
     /// Model keys the user has hidden from the picker.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub hidden_models: Vec<String>,
+
+    /// How the OpenCode sidecar is restarted after an unexpected exit.
+    #[serde(default)]
+    pub restart_policy: RestartPolicy,
+
+    /// Liveness probing of a running sidecar.
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+
+    /// How long a freshly spawned sidecar may take to become healthy.
+    #[serde(default)]
+    pub startup: StartupConfig,
+
+    /// Port blocks reserved for the OpenCode server, the MCP bridge, the
+    /// launcher control endpoint and the Rojo server.
+    #[serde(default)]
+    pub ports: PortConfig,
+
+    /// The managed `rojo serve` process for Rojo projects.
+    #[serde(default)]
+    pub rojo: RojoConfig,
+
+    /// User-defined agents added next to the built-in ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_agents: Vec<CustomAgent>,
+
+    /// roblox-studio MCP tool name or glob (`*`, `?`) → what OpenCode does
+    /// when the AI calls a matching tool. See `studio_tools::resolve`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tool_policy: BTreeMap<String, ToolAction>,
+
+    /// Extra MCP servers, keyed by name, merged into the generated `mcp`
+    /// map next to the bundled roblox-studio server.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mcp_servers: BTreeMap<String, McpServerConfig>,
+
+    /// Pinned OpenCode plugins (`name@1.2.3`). `None` loads the plugins
+    /// bundled with the app; an empty list loads none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugins: Option<Vec<String>>,
+
+    /// OpenAI-compatible servers on the local network (Ollama, LM Studio,
+    /// llama.cpp, …), added to OpenCode's `provider` map.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub local_providers: Vec<LocalProvider>,
+
+    /// Projects besides the default one, which is the workspace root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<Project>,
+
+    /// Name of the active project; `None` is the default project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_project: Option<String>,
+
+    /// Absolute workspace root; `None` uses `~/BloxBot`. The `BLOXBOT_HOME`
+    /// environment variable takes precedence. Change it with
+    /// `migrate_workspace` so existing data moves along.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_root: Option<PathBuf>,
+}
+
+impl AppConfig {
+    /// Check cross-field constraints that serde can't express.
+    pub fn validate(&self) -> Result<(), String> {
+        self.ports.validate()?;
+        self.rojo.validate()?;
+        for pattern in self.tool_policy.keys() {
+            crate::studio_tools::validate_pattern(pattern)?;
+        }
+        let mut agent_names = HashSet::new();
+        for agent in &self.custom_agents {
+            agent.validate()?;
+            if !agent_names.insert(agent.name.to_ascii_lowercase()) {
+                return Err(format!("Duplicate custom agent name \"{}\"", agent.name));
+            }
+        }
+        for (name, server) in &self.mcp_servers {
+            server.validate(name)?;
+        }
+        for spec in self.plugins.iter().flatten() {
+            crate::plugins::parse_spec(spec)?;
+        }
+        let mut provider_ids = HashSet::new();
+        for provider in &self.local_providers {
+            provider.validate()?;
+            if !provider_ids.insert(provider.id.to_ascii_lowercase()) {
+                return Err(format!("Duplicate local provider id \"{}\"", provider.id));
+            }
+        }
+        let mut project_names = HashSet::new();
+        for project in &self.projects {
+            project.validate()?;
+            if !project_names.insert(project.name.to_ascii_lowercase()) {
+                return Err(format!("Duplicate project name \"{}\"", project.name));
+            }
+        }
+        if let Some(current) = &self.current_project {
+            if !self.projects.iter().any(|p| &p.name == current) {
+                return Err(format!("Unknown current project \"{current}\""));
+            }
+        }
+        if let Some(root) = &self.workspace_root {
+            if !root.is_absolute() {
+                return Err(format!(
+                    "workspaceRoot must be an absolute path, got \"{}\"",
+                    root.display()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// OpenCode permission outcome for a tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolAction {
+    /// Run without asking.
+    Allow,
+    /// Ask the user to approve each call.
+    Ask,
+    /// Never run.
+    Deny,
+}
+
+/// How OpenCode offers an agent: as a selectable primary agent, as a
+/// subagent the primary agent can delegate to, or both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentMode {
+    #[default]
+    Primary,
+    Subagent,
+    All,
+}
+
+/// A user-defined OpenCode agent, merged into the generated `agent` map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomAgent {
+    /// Key in OpenCode's `agent` map; shown in the agent picker.
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// System prompt.
+    pub prompt: String,
+
+    /// `"providerID/modelID"`; the user's selected model if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    #[serde(default)]
+    pub mode: AgentMode,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+
+    /// Tool names or globs (e.g. `"roblox-studio_get_*"`) the agent may
+    /// use. `None` allows every tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+}
+
+/// Check a name that becomes a key in OpenCode's config. `kind` starts
+/// the message ("Agent name").
+fn validate_name(kind: &str, name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "{kind} \"{name}\" must be non-empty and use only letters, digits, '-' and '_'"
+        ));
+    }
+    Ok(())
+}
+
+impl CustomAgent {
+    pub fn validate(&self) -> Result<(), String> {
+        let name = &self.name;
+        validate_name("Agent name", name)?;
+        if crate::agents::is_builtin(name) {
+            return Err(format!(
+                "Agent name \"{name}\" is reserved for a built-in agent"
+            ));
+        }
+        if self.prompt.trim().is_empty() {
+            return Err(format!("Agent \"{name}\" needs a prompt"));
+        }
+        if let Some(model) = &self.model {
+            if !model.contains('/') {
+                return Err(format!(
+                    "Agent \"{name}\": model must be \"providerID/modelID\", got \"{model}\""
+                ));
+            }
+        }
+        if let Some(t) = self.temperature {
+            if !(0.0..=2.0).contains(&t) {
+                return Err(format!(
+                    "Agent \"{name}\": temperature must be between 0 and 2, got {t}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A user-defined MCP server. OpenCode prefixes its tools with the server
+/// name, like `roblox-studio_*` for the bundled one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    #[serde(flatten)]
+    pub transport: McpTransport,
+}
+
+/// How OpenCode reaches an MCP server; tagged with `type` like OpenCode's
+/// own config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum McpTransport {
+    /// A process OpenCode spawns and talks to over stdio. It runs with the
+    /// sidecar's isolated `PATH`, so `node`/`npx` resolve to the bundled
+    /// Node.js.
+    Local {
+        command: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+    },
+    /// A server reachable over HTTP (streamable HTTP or SSE).
+    Remote {
+        url: String,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl McpServerConfig {
+    pub fn validate(&self, name: &str) -> Result<(), String> {
+        validate_name("MCP server name", name)?;
+        if name.eq_ignore_ascii_case(crate::studio_tools::MCP_SERVER_NAME) {
+            return Err(format!(
+                "MCP server name \"{name}\" is reserved for the bundled Studio server"
+            ));
+        }
+        match &self.transport {
+            McpTransport::Local { command, env, .. } => {
+                if command.trim().is_empty() {
+                    return Err(format!("MCP server \"{name}\" needs a command"));
+                }
+                if let Some(key) = env.keys().find(|k| k.eq_ignore_ascii_case("PATH")) {
+                    return Err(format!(
+                        "MCP server \"{name}\": env may not set {key}; local servers use BloxBot's isolated PATH"
+                    ));
+                }
+            }
+            McpTransport::Remote { url, .. } => {
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(format!(
+                        "MCP server \"{name}\": url must start with http:// or https://, got \"{url}\""
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An OpenAI-compatible model server, e.g. Ollama at
+/// `http://192.168.1.20:11434/v1` or LM Studio at `http://localhost:1234/v1`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalProvider {
+    /// Key in OpenCode's `provider` map; models are selected as `id/model`.
+    pub id: String,
+
+    /// Shown in the model picker; defaults to `id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Base URL of the OpenAI-compatible API, usually ending in `/v1`.
+    pub base_url: String,
+
+    /// Sent as a bearer token; most local servers don't need one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+
+    pub models: Vec<LocalModel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModel {
+    /// Model ID as the server knows it (e.g. `qwen2.5-coder:14b`).
+    pub id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Context window in tokens, as configured on the server. OpenCode
+    /// uses it to decide when to compact the conversation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_size: Option<u32>,
+
+    /// Maximum tokens per response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output: Option<u32>,
+}
+
+impl LocalProvider {
+    pub fn validate(&self) -> Result<(), String> {
+        let id = &self.id;
+        validate_name("Provider id", id)?;
+        if !(self.base_url.starts_with("http://") || self.base_url.starts_with("https://")) {
+            return Err(format!(
+                "Provider \"{id}\": baseUrl must start with http:// or https://, got \"{}\"",
+                self.base_url
+            ));
+        }
+        if self.models.is_empty() {
+            return Err(format!("Provider \"{id}\" needs at least one model"));
+        }
+        for (i, model) in self.models.iter().enumerate() {
+            if model.id.trim().is_empty() {
+                return Err(format!("Provider \"{id}\" has a model without an id"));
+            }
+            if self.models[..i].iter().any(|other| other.id == model.id) {
+                return Err(format!(
+                    "Provider \"{id}\" lists model \"{}\" twice",
+                    model.id
+                ));
+            }
+            if model.context_size == Some(0) || model.max_output == Some(0) {
+                return Err(format!(
+                    "Provider \"{id}\": model \"{}\" needs a positive context size and output limit",
+                    model.id
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A project with its own directory, OpenCode sessions and `.opencode`
+/// data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub name: String,
+
+    /// Absolute directory for projects kept elsewhere. `None` places the
+    /// project under `projects/` in the workspace root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl Project {
+    pub fn validate(&self) -> Result<(), String> {
+        let name = &self.name;
+        validate_name("Project name", name)?;
+        if name.eq_ignore_ascii_case(crate::projects::DEFAULT_PROJECT) {
+            return Err(format!("Project name \"{name}\" is reserved"));
+        }
+        if let Some(path) = &self.path {
+            if !path.is_absolute() {
+                return Err(format!(
+                    "Project \"{name}\": path must be absolute, got \"{}\"",
+                    path.display()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Supervisor settings for automatically restarting a crashed sidecar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RestartPolicy {
+    /// Consecutive restart attempts before giving up and showing an error.
+    /// `0` disables automatic restarts entirely.
+    pub max_attempts: u32,
+
+    /// Delay before the first restart attempt. Doubles on every
+    /// subsequent attempt.
+    pub initial_backoff_ms: u64,
+
+    /// Upper bound for the exponential backoff delay.
+    pub max_backoff_ms: u64,
+
+    /// If the server stayed healthy for at least this long before
+    /// crashing, the attempt counter starts over from zero.
+    pub reset_window_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 30_000,
+            reset_window_secs: 120,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Backoff delay before restart attempt `attempt` (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(31);
+        let ms = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << exp)
+            .min(self.max_backoff_ms.max(self.initial_backoff_ms));
+        Duration::from_millis(ms)
+    }
+
+    pub fn reset_window(&self) -> Duration {
+        Duration::from_secs(self.reset_window_secs)
+    }
+}
+
+/// Background liveness checks for a sidecar that already passed its
+/// startup health check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchdogConfig {
+    pub enabled: bool,
+
+    /// Time between probes of `/global/health` and `/mcp`.
+    pub interval_ms: u64,
+
+    /// Consecutive failed probes before the sidecar is restarted.
+    pub failure_threshold: u32,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_ms: 5_000,
+            failure_threshold: 3,
+        }
+    }
+}
+
+/// Deadline for the startup health check. The sidecar always gets
+/// `health_timeout_ms`; past that it keeps getting more time for as long
+/// as it is still printing output (first-launch plugin installs,
+/// migrations), up to `max_health_timeout_ms`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StartupConfig {
+    pub health_timeout_ms: u64,
+
+    /// Give up once the sidecar has been silent for this long after the
+    /// base deadline passed. Never applies before its first output line.
+    pub stall_timeout_ms: u64,
+
+    /// Hard upper bound, however chatty the sidecar is.
+    pub max_health_timeout_ms: u64,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            health_timeout_ms: 7_500,
+            stall_timeout_ms: 15_000,
+            max_health_timeout_ms: 300_000,
+        }
+    }
+}
+
+impl StartupConfig {
+    pub fn health_timeout(&self) -> Duration {
+        Duration::from_millis(self.health_timeout_ms)
+    }
+
+    pub fn stall_timeout(&self) -> Duration {
+        Duration::from_millis(self.stall_timeout_ms)
+    }
+
+    pub fn max_health_timeout(&self) -> Duration {
+        Duration::from_millis(self.max_health_timeout_ms.max(self.health_timeout_ms))
+    }
+}
+
+/// Base ports and block size for the four servers BloxBot runs. The
+/// defaults sit in the IANA dynamic/private range (49152-65535):
+///
+/// 59200-59209: OpenCode server (HTTP API)
+/// 59210-59219: MCP bridge (Studio plugin ↔ MCP server)
+/// 59220-59229: MCP launcher control endpoint
+/// 59230-59239: Rojo server (Studio Rojo plugin ↔ `rojo serve`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PortConfig {
+    pub opencode_start: u16,
+    pub bridge_start: u16,
+    pub control_start: u16,
+    pub rojo_start: u16,
+
+    /// Number of consecutive ports tried in each block before falling
+    /// back to an ephemeral port.
+    pub range_size: u16,
+}
+
+impl Default for PortConfig {
+    fn default() -> Self {
+        Self {
+            opencode_start: 59200,
+            bridge_start: 59210,
+            control_start: 59220,
+            rojo_start: 59230,
+            range_size: 10,
+        }
+    }
+}
+
+/// Largest allowed `range_size`. Cleanup probes every port in every
+/// block, so this also bounds how long that takes.
+const MAX_PORT_RANGE: u16 = 100;
+
+impl PortConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.range_size == 0 || self.range_size > MAX_PORT_RANGE {
+            return Err(format!(
+                "ports.rangeSize must be between 1 and {MAX_PORT_RANGE}, got {}",
+                self.range_size
+            ));
+        }
+
+        let blocks = [
+            ("opencodeStart", self.opencode_start),
+            ("bridgeStart", self.bridge_start),
+            ("controlStart", self.control_start),
+            ("rojoStart", self.rojo_start),
+        ];
+        for (name, start) in blocks {
+            if start < 1024 {
+                return Err(format!(
+                    "ports.{name} must be 1024 or higher (privileged ports are not allowed), got {start}"
+                ));
+            }
+            if u32::from(start) + u32::from(self.range_size) > 65536 {
+                return Err(format!(
+                    "ports.{name} {start} with rangeSize {} runs past port 65535",
+                    self.range_size
+                ));
+            }
+        }
+
+        for (i, (a_name, a)) in blocks.iter().enumerate() {
+            for (b_name, b) in &blocks[i + 1..] {
+                if a.abs_diff(*b) < self.range_size {
+                    return Err(format!(
+                        "ports.{a_name} ({a}) and ports.{b_name} ({b}) overlap with rangeSize {}",
+                        self.range_size
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The managed Rojo server (see `rojo`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RojoConfig {
+    /// Run `rojo serve` when the workspace has a `default.project.json`.
+    pub enabled: bool,
+
+    /// Absolute path of the `rojo` executable. `None` looks it up on
+    /// `PATH` and in the Rokit, Aftman and Foreman tool directories.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl Default for RojoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+        }
+    }
+}
+
+impl RojoConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(path) = &self.path {
+            if !path.is_absolute() {
+                return Err(format!(
+                    "rojo.path must be an absolute path, got \"{}\"",
+                    path.display()
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 // ── File path ───────────────────────────────────────────────────────────
@@ -62,7 +676,7 @@ fn cache() -> &'static Mutex<AppConfig> {
 /// If the file doesn't exist, creates it with defaults.
 pub fn load(app: &AppHandle) -> Result<(), String> {
     let path = config_path(app)?;
-    let cfg = if path.exists() {
+    let mut cfg = if path.exists() {
         let bytes =
             std::fs::read(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
         serde_json::from_slice::<AppConfig>(&bytes).unwrap_or_else(|e| {
@@ -75,6 +689,7 @@ pub fn load(app: &AppHandle) -> Result<(), String> {
         save_to_disk(&path, &cfg);
         cfg
     };
+    drop_invalid(&mut cfg);
 
     log::info!("Config loaded: has_launched={}", cfg.has_launched);
 
@@ -83,11 +698,129 @@ pub fn load(app: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Reset or drop whatever fails validation in a config read from disk,
+/// logging each, so one bad entry doesn't cost the user the rest.
+fn drop_invalid(cfg: &mut AppConfig) {
+    if let Err(e) = cfg.ports.validate() {
+        log::warn!("Invalid port settings, using defaults: {e}");
+        cfg.ports = PortConfig::default();
+    }
+    if let Err(e) = cfg.rojo.validate() {
+        log::warn!("Ignoring invalid Rojo path: {e}");
+        cfg.rojo.path = None;
+    }
+    cfg.tool_policy.retain(
+        |pattern, _| match crate::studio_tools::validate_pattern(pattern) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Ignoring invalid tool policy entry: {e}");
+                false
+            }
+        },
+    );
+    let mut agent_names = HashSet::new();
+    cfg.custom_agents.retain(|agent| match agent.validate() {
+        Ok(()) if agent_names.insert(agent.name.to_ascii_lowercase()) => true,
+        Ok(()) => {
+            log::warn!("Ignoring duplicate custom agent \"{}\"", agent.name);
+            false
+        }
+        Err(e) => {
+            log::warn!("Ignoring invalid custom agent: {e}");
+            false
+        }
+    });
+
+    cfg.mcp_servers
+        .retain(|name, server| match server.validate(name) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Ignoring invalid MCP server: {e}");
+                false
+            }
+        });
+
+    if let Some(plugins) = &mut cfg.plugins {
+        plugins.retain(|spec| match crate::plugins::parse_spec(spec) {
+            Ok(_) => true,
+            Err(e) => {
+                log::warn!("Ignoring invalid plugin entry: {e}");
+                false
+            }
+        });
+    }
+
+    let mut provider_ids = HashSet::new();
+    cfg.local_providers
+        .retain(|provider| match provider.validate() {
+            Ok(()) if provider_ids.insert(provider.id.to_ascii_lowercase()) => true,
+            Ok(()) => {
+                log::warn!("Ignoring duplicate local provider \"{}\"", provider.id);
+                false
+            }
+            Err(e) => {
+                log::warn!("Ignoring invalid local provider: {e}");
+                false
+            }
+        });
+    let mut project_names = HashSet::new();
+    cfg.projects.retain(|project| match project.validate() {
+        Ok(()) if project_names.insert(project.name.to_ascii_lowercase()) => true,
+        Ok(()) => {
+            log::warn!("Ignoring duplicate project \"{}\"", project.name);
+            false
+        }
+        Err(e) => {
+            log::warn!("Ignoring invalid project: {e}");
+            false
+        }
+    });
+    if let Some(current) = &cfg.current_project {
+        if !cfg.projects.iter().any(|p| &p.name == current) {
+            log::warn!("Unknown current project \"{current}\", using the default project");
+            cfg.current_project = None;
+        }
+    }
+
+    if cfg
+        .workspace_root
+        .as_ref()
+        .is_some_and(|root| !root.is_absolute())
+    {
+        log::warn!("Ignoring relative workspaceRoot, using the default");
+        cfg.workspace_root = None;
+    }
+}
+
 /// Read a snapshot of the current config. Lock-free clone.
 pub fn get() -> AppConfig {
     cache().lock().unwrap().clone()
 }
 
+/// Replace the in-memory config without saving it.
+#[cfg(test)]
+pub fn set(cfg: AppConfig) {
+    *cache().lock().unwrap() = cfg;
+}
+
+/// Modify the config through `f`, validate the result and persist it.
+/// Nothing changes if `f` or validation fails.
+pub fn update(
+    app: &AppHandle,
+    f: impl FnOnce(&mut AppConfig) -> Result<(), String>,
+) -> Result<AppConfig, String> {
+    let path = config_path(app)?;
+    let mut guard = cache().lock().unwrap();
+
+    let mut updated = guard.clone();
+    f(&mut updated)?;
+    updated.validate()?;
+
+    save_to_disk(&path, &updated);
+    *guard = updated.clone();
+    Ok(updated)
+}
+
 fn save_to_disk(path: &PathBuf, cfg: &AppConfig) {
     if let Some(parent) = path.parent() {
         let _ = std::fs::create_dir_all(parent);
@@ -113,21 +846,233 @@ pub fn get_config() -> AppConfig {
 /// Only the fields present in the input are updated; the rest stay as-is.
 #[tauri::command]
 pub fn set_config(app: AppHandle, patch: serde_json::Value) -> Result<AppConfig, String> {
-    let path = config_path(&app)?;
-    let mut guard = cache().lock().unwrap();
+    update(&app, |cfg| {
+        // Serialize current state → JSON value → merge patch → deserialize back
+        let mut current =
+            serde_json::to_value(&*cfg).map_err(|e| format!("Config serialization failed: {e}"))?;
+        if let (Some(base), Some(patch_obj)) = (current.as_object_mut(), patch.as_object()) {
+            for (k, v) in patch_obj {
+                base.insert(k.clone(), v.clone());
+            }
+        }
+        *cfg =
+            serde_json::from_value(current).map_err(|e| format!("Invalid config values: {e}"))?;
+        Ok(())
+    })
+}
 
-    // Serialize current state → JSON value → merge patch → deserialize back
-    let mut current =
-        serde_json::to_value(&*guard).map_err(|e| format!("Config serialization failed: {e}"))?;
-    if let (Some(base), Some(patch_obj)) = (current.as_object_mut(), patch.as_object()) {
-        for (k, v) in patch_obj {
-            base.insert(k.clone(), v.clone());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(name: &str) -> CustomAgent {
+        CustomAgent {
+            name: name.to_string(),
+            description: None,
+            prompt: "Review the change.".to_string(),
+            model: None,
+            mode: AgentMode::Subagent,
+            temperature: None,
+            allowed_tools: None,
         }
     }
-    let updated: AppConfig =
-        serde_json::from_value(current).map_err(|e| format!("Invalid config values: {e}"))?;
 
-    save_to_disk(&path, &updated);
-    *guard = updated.clone();
-    Ok(updated)
+    fn provider(id: &str) -> LocalProvider {
+        LocalProvider {
+            id: id.to_string(),
+            name: None,
+            base_url: "http://localhost:11434/v1".to_string(),
+            api_key: None,
+            models: vec![LocalModel {
+                id: "qwen2.5-coder:14b".to_string(),
+                name: None,
+                context_size: None,
+                max_output: None,
+            }],
+        }
+    }
+
+    fn project(name: &str) -> Project {
+        Project {
+            name: name.to_string(),
+            path: None,
+        }
+    }
+
+    #[test]
+    fn names_are_checked_for_file_and_key_safe_characters() {
+        assert!(validate_name("Agent name", "code-review_2").is_ok());
+        for bad in ["", "code review", "a/b", "ré"] {
+            let err = validate_name("Agent name", bad).unwrap_err();
+            assert!(err.starts_with(&format!("Agent name \"{bad}\"")), "{err}");
+        }
+    }
+
+    #[test]
+    fn duplicate_custom_agents_are_rejected_ignoring_case() {
+        let mut cfg = AppConfig {
+            custom_agents: vec![agent("reviewer"), agent("tester")],
+            ..AppConfig::default()
+        };
+        assert!(cfg.validate().is_ok());
+
+        cfg.custom_agents.push(agent("Reviewer"));
+        let err = cfg.validate().unwrap_err();
+        assert!(
+            err.contains("Duplicate custom agent name \"Reviewer\""),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn invalid_custom_agents_are_dropped_on_load() {
+        let mut cfg = AppConfig {
+            custom_agents: vec![
+                agent("reviewer"),
+                agent("REVIEWER"),
+                agent("bad name"),
+                agent("build"),
+                agent("tester"),
+            ],
+            ..AppConfig::default()
+        };
+        drop_invalid(&mut cfg);
+        let names: Vec<_> = cfg.custom_agents.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["reviewer", "tester"]);
+    }
+
+    #[test]
+    fn duplicate_local_providers_are_rejected_ignoring_case() {
+        let mut cfg = AppConfig {
+            local_providers: vec![provider("ollama"), provider("lmstudio")],
+            ..AppConfig::default()
+        };
+        assert!(cfg.validate().is_ok());
+
+        cfg.local_providers.push(provider("Ollama"));
+        let err = cfg.validate().unwrap_err();
+        assert!(
+            err.contains("Duplicate local provider id \"Ollama\""),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn invalid_local_providers_are_dropped_on_load() {
+        let mut no_models = provider("empty");
+        no_models.models.clear();
+        let mut cfg = AppConfig {
+            local_providers: vec![
+                provider("ollama"),
+                provider("OLLAMA"),
+                no_models,
+                provider("lm studio"),
+                provider("lmstudio"),
+            ],
+            ..AppConfig::default()
+        };
+        drop_invalid(&mut cfg);
+        let ids: Vec<_> = cfg.local_providers.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["ollama", "lmstudio"]);
+    }
+
+    #[test]
+    fn duplicate_projects_are_rejected_ignoring_case() {
+        let mut cfg = AppConfig {
+            projects: vec![project("obby"), project("tycoon")],
+            ..AppConfig::default()
+        };
+        assert!(cfg.validate().is_ok());
+
+        cfg.projects.push(project("Obby"));
+        let err = cfg.validate().unwrap_err();
+        assert!(err.contains("Duplicate project name \"Obby\""), "{err}");
+    }
+
+    #[test]
+    fn invalid_projects_are_dropped_on_load() {
+        let mut relative = project("relative");
+        relative.path = Some(PathBuf::from("games/relative"));
+        let mut cfg = AppConfig {
+            projects: vec![
+                project("obby"),
+                project("OBBY"),
+                project("Default"),
+                relative,
+                project("tycoon"),
+            ],
+            current_project: Some("relative".to_string()),
+            ..AppConfig::default()
+        };
+        drop_invalid(&mut cfg);
+        let names: Vec<_> = cfg.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["obby", "tycoon"]);
+        assert_eq!(cfg.current_project, None);
+    }
+
+    #[test]
+    fn default_ports_are_valid() {
+        assert!(PortConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn port_range_size_must_be_between_one_and_the_maximum() {
+        for range_size in [0, MAX_PORT_RANGE + 1] {
+            let ports = PortConfig {
+                range_size,
+                ..PortConfig::default()
+            };
+            let err = ports.validate().unwrap_err();
+            assert!(err.starts_with("ports.rangeSize must be between"), "{err}");
+        }
+
+        let ports = PortConfig {
+            range_size: 1,
+            ..PortConfig::default()
+        };
+        assert!(ports.validate().is_ok());
+    }
+
+    #[test]
+    fn overlapping_port_blocks_are_rejected() {
+        let ports = PortConfig {
+            bridge_start: 59205,
+            ..PortConfig::default()
+        };
+        let err = ports.validate().unwrap_err();
+        assert!(
+            err.contains("ports.opencodeStart (59200) and ports.bridgeStart (59205) overlap"),
+            "{err}"
+        );
+
+        // Overlap is checked between every pair, not just neighbours.
+        let ports = PortConfig {
+            rojo_start: 59203,
+            ..PortConfig::default()
+        };
+        let err = ports.validate().unwrap_err();
+        assert!(
+            err.contains("ports.opencodeStart (59200) and ports.rojoStart (59203) overlap"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn port_blocks_must_end_at_or_before_65535() {
+        let last_fit = PortConfig {
+            rojo_start: 65526,
+            ..PortConfig::default()
+        };
+        assert!(last_fit.validate().is_ok());
+
+        let ports = PortConfig {
+            rojo_start: 65527,
+            ..PortConfig::default()
+        };
+        let err = ports.validate().unwrap_err();
+        assert!(
+            err.contains("ports.rojoStart 65527 with rangeSize 10 runs past port 65535"),
+            "{err}"
+        );
+    }
 }
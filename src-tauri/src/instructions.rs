@@ -0,0 +1,178 @@
+//! Project instruction files.
+//!
+//! House rules that should apply to every chat — which networking library
+//! to use, naming schemes, folder layout — live in Markdown files in the
+//! workspace instead of being repeated in each conversation:
+//!
+//! - `BLOXBOT.md` in the workspace root
+//! - every `*.md` file in `.bloxbot/rules/`
+//!
+//! The files found are listed in the generated config's `instructions`, and
+//! OpenCode appends them to every agent's system prompt. `AGENTS.md` works
+//! too, but OpenCode loads it from the project directory on its own, so it
+//! is not listed again. A background task watches the files and reloads
+//! OpenCode's configuration when one is added, removed or edited — once no
+//! session is mid-turn, since the agent may well be the one editing them.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Manager};
+
+use crate::opencode::SharedOpenCodeState;
+
+/// Instruction files looked up in the workspace root, in prompt order.
+pub const ROOT_FILES: &[&str] = &["BLOXBOT.md"];
+
+/// Directory (relative to the workspace) whose `*.md` files are rules.
+pub const RULES_DIR: &str = ".bloxbot/rules";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Set while the workspace is being moved (see `pause_watcher`).
+static WATCHER_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Instruction files present in `workspace`: the root files first, then
+/// the rules in file-name order.
+pub fn detect(workspace: &Path) -> Vec<PathBuf> {
+    let mut found: Vec<PathBuf> = ROOT_FILES
+        .iter()
+        .map(|name| workspace.join(name))
+        .filter(|path| path.is_file())
+        .collect();
+
+    let rules_dir = workspace.join(RULES_DIR);
+    if let Ok(entries) = std::fs::read_dir(&rules_dir) {
+        let mut rules: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+            })
+            .collect();
+        rules.sort();
+        found.extend(rules);
+    }
+    found
+}
+
+/// The `instructions` entries for `workspace`.
+pub fn config_entries(workspace: &Path) -> Vec<String> {
+    detect(workspace)
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Path, modification time and size of every instruction file.
+type Fingerprint = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+/// Changes whenever an instruction file is added, removed or edited.
+fn fingerprint(workspace: &Path) -> Fingerprint {
+    detect(workspace)
+        .into_iter()
+        .map(|path| {
+            let meta = std::fs::metadata(&path).ok();
+            let modified = meta.as_ref().and_then(|m| m.modified().ok());
+            let len = meta.map_or(0, |m| m.len());
+            (path, modified, len)
+        })
+        .collect()
+}
+
+/// Stop the watcher from looking at the workspace until called again with
+/// `false`. It starts over from the files it finds then.
+pub fn pause_watcher(paused: bool) {
+    WATCHER_PAUSED.store(paused, Ordering::SeqCst);
+}
+
+/// Watch the instruction files and reload OpenCode's configuration when
+/// they change. Call once from `setup`.
+pub fn spawn_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<SharedOpenCodeState>().inner().clone();
+        let mut last: Option<(PathBuf, Fingerprint)> = None;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            if WATCHER_PAUSED.load(Ordering::SeqCst) {
+                last = None;
+                continue;
+            }
+            // Resolved on every tick, since switching projects changes it.
+            let Ok(workspace) = crate::paths::workspace_dir() else {
+                continue;
+            };
+            let current = fingerprint(&workspace);
+            match &last {
+                Some((dir, previous)) if *dir == workspace => {
+                    if *previous == current {
+                        continue;
+                    }
+                }
+                // First look at this project: the config written when
+                // OpenCode started already lists these files.
+                _ => {
+                    last = Some((workspace, current));
+                    continue;
+                }
+            }
+            log::info!(
+                "Instruction files changed ({} file(s)); reloading OpenCode config once idle",
+                current.len()
+            );
+            crate::opencode::reconfigure_when_idle(&state, &app, "Instruction files");
+            last = Some((workspace, current));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn detect_lists_the_root_file_then_sorted_rules() {
+        let temp = TempDir::new("instructions-detect");
+        let workspace = temp.path();
+        assert!(detect(workspace).is_empty());
+
+        let rules = workspace.join(RULES_DIR);
+        std::fs::create_dir_all(rules.join("nested.md")).unwrap();
+        for name in [
+            "naming.md",
+            "Audio.MD",
+            "b-networking.md",
+            "notes.txt",
+            ".md.bak",
+        ] {
+            std::fs::write(rules.join(name), "rule").unwrap();
+        }
+        std::fs::write(workspace.join("BLOXBOT.md"), "house rules").unwrap();
+        std::fs::write(workspace.join("AGENTS.md"), "loaded by OpenCode").unwrap();
+
+        assert_eq!(
+            detect(workspace),
+            [
+                workspace.join("BLOXBOT.md"),
+                rules.join("Audio.MD"),
+                rules.join("b-networking.md"),
+                rules.join("naming.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprint_changes_when_a_rule_is_edited() {
+        let temp = TempDir::new("instructions-fingerprint");
+        let workspace = temp.path();
+        std::fs::write(workspace.join("BLOXBOT.md"), "short").unwrap();
+        let before = fingerprint(workspace);
+        std::fs::write(workspace.join("BLOXBOT.md"), "a longer rule").unwrap();
+        assert_ne!(fingerprint(workspace), before);
+    }
+}
@@ -1,16 +1,43 @@
+mod agents;
+mod checkpoints;
 mod config;
+mod instructions;
 mod logging;
+mod metrics;
 mod opencode;
+mod opencode_config;
 mod paths;
+mod plugins;
+mod ports;
+mod process_registry;
+mod projects;
+mod prompts;
+mod providers;
+mod rojo;
+mod script_sync;
+mod shutdown;
+mod studio_bridge;
+mod studio_tools;
+#[cfg(test)]
+mod test_util;
+mod workspace;
 
 use opencode::SharedOpenCodeState;
 use std::sync::Arc;
 use tauri::menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder};
 use tauri::webview::WebviewWindowBuilder;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tauri_plugin_opener::OpenerExt;
 use tokio::sync::Mutex;
 
+/// Payload of the `second-instance` event: the arguments and working
+/// directory of a launch that was redirected to this instance.
+#[derive(Clone, serde::Serialize)]
+struct SecondInstancePayload {
+    args: Vec<String>,
+    cwd: String,
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialise the logger before anything else so the very first
@@ -21,6 +48,18 @@ pub fn run() {
         Arc::new(Mutex::new(opencode::OpenCodeState::default()));
 
     tauri::Builder::default()
+        // Must be registered first: a second launch exits inside this
+        // plugin, before `setup` runs, so it never starts (or cleans up)
+        // a second OpenCode/MCP stack under the first instance.
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            log::info!("Second instance launched with args {args:?}; focusing existing window");
+            if let Some(win) = app.get_webview_window("main") {
+                let _ = win.unminimize();
+                let _ = win.show();
+                let _ = win.set_focus();
+            }
+            let _ = app.emit("second-instance", SecondInstancePayload { args, cwd });
+        }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_store::Builder::new().build())
@@ -36,12 +75,43 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             config::get_config,
             config::set_config,
+            agents::list_custom_agents,
+            agents::create_custom_agent,
+            agents::update_custom_agent,
+            agents::delete_custom_agent,
             logging::get_logs,
+            metrics::get_opencode_metrics,
             opencode::get_opencode_status,
             opencode::restart_opencode,
+            opencode::reconfigure_opencode,
             opencode::poll_studio_status,
+            opencode::poll_mcp_servers,
             opencode::shutdown_mcp,
             opencode::get_mcp_url,
+            opencode_config::get_effective_opencode_config,
+            studio_tools::list_studio_tools,
+            studio_tools::set_tool_policy,
+            prompts::get_studio_prompt,
+            prompts::set_studio_prompt,
+            prompts::diff_studio_prompt,
+            prompts::reset_studio_prompt,
+            projects::list_projects,
+            projects::create_project,
+            projects::switch_project,
+            projects::remove_project,
+            workspace::migrate_workspace,
+            providers::probe_local_provider,
+            rojo::get_rojo_status,
+            rojo::restart_rojo,
+            script_sync::sync_scripts,
+            script_sync::stop_script_sync,
+            script_sync::get_script_sync_status,
+            script_sync::resolve_script_conflict,
+            checkpoints::create_checkpoint,
+            checkpoints::list_checkpoints,
+            checkpoints::diff_checkpoint,
+            checkpoints::restore_checkpoint,
+            checkpoints::delete_checkpoints,
             paths::get_workspace_dir,
             paths::check_plugin_installed,
             paths::check_plugin_needs_update,
@@ -59,6 +129,9 @@ pub fn run() {
             if let Err(e) = config::load(app.handle()) {
                 log::error!("Failed to load config: {e}");
             }
+            if let Err(e) = projects::migrate_legacy_default() {
+                log::error!("Failed to move legacy files into the default project: {e}");
+            }
 
             // ── Application menu ──────────────────────────────────
             let app_submenu = SubmenuBuilder::new(app, "BloxBot")
@@ -153,6 +226,15 @@ pub fn run() {
             app.handle()
                 .plugin(tauri_plugin_updater::Builder::new().build())?;
 
+            // ── Graceful shutdown on OS signals ───────────────────
+            shutdown::listen_for_signals(app.handle().clone());
+
+            // ── Resource metrics for the debug-logs window ────────
+            metrics::spawn_emitter(app.handle().clone());
+
+            // ── Reload project instruction files on change ────────
+            instructions::spawn_watcher(app.handle().clone());
+
             // ── Auto-start OpenCode server ────────────────────────
             let state = app.state::<SharedOpenCodeState>().inner().clone();
             let handle = app.handle().clone();
@@ -173,21 +255,13 @@ pub fn run() {
             }
 
             if let tauri::WindowEvent::CloseRequested { .. } = event {
-                // Gracefully tear down the full process tree (MCP server →
-                // launcher → OpenCode sidecar) before exiting.
-                let state = window
-                    .app_handle()
-                    .state::<SharedOpenCodeState>()
-                    .inner()
-                    .clone();
-                let handle = window.app_handle().clone();
-                tauri::async_runtime::block_on(async {
-                    opencode::stop_all(&state, &handle).await;
-                });
-                // Exit the entire app (closes all windows including debug-logs).
-                window.app_handle().exit(0);
+                // Exit the entire app (closes all windows including
+                // debug-logs). The shutdown coordinator tears down the
+                // process tree before the exit goes through.
+                shutdown::request_exit(window.app_handle(), "main window closed");
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running BloxBot");
+        .build(tauri::generate_context!())
+        .expect("error while building BloxBot")
+        .run(shutdown::handle_run_event);
 }
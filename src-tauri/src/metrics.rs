@@ -0,0 +1,317 @@
+//! Resource usage of the OpenCode process tree.
+//!
+//! `get_opencode_metrics` samples CPU, memory, open files and uptime of the
+//! sidecar, the MCP launcher and the MCP server, using the PIDs tracked in
+//! `OpenCodeState`. While the debug-logs window is open the same snapshot
+//! is pushed to it every `EMIT_INTERVAL` as an `opencode-metrics` event.
+//!
+//! Like the rest of the process code this shells out to platform tools:
+//! - macOS/Linux: `ps -o time=,etime=,rss=`, plus `/proc/<pid>/fd` on Linux
+//!   or `lsof -p` on macOS for the open file count
+//! - Windows: `Get-Process` via PowerShell
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::opencode::SharedOpenCodeState;
+use crate::process_registry::ProcessRole;
+
+/// How often metrics are pushed to the debug-logs window.
+const EMIT_INTERVAL: Duration = Duration::from_secs(2);
+
+const DEBUG_LOGS_LABEL: &str = "debug-logs";
+
+// ── Types ───────────────────────────────────────────────────────────────
+
+/// Resource usage of one process.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessMetrics {
+    pub role: ProcessRole,
+    pub pid: u32,
+    /// Share of one CPU core since the previous sample (over the whole
+    /// process lifetime for the first sample of a PID).
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    /// Open file descriptors (handles on Windows), if they could be counted.
+    pub open_files: Option<u64>,
+    pub uptime_secs: u64,
+}
+
+/// Snapshot returned by `get_opencode_metrics` and the `opencode-metrics`
+/// event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenCodeMetrics {
+    /// Only processes that are currently known and alive.
+    pub processes: Vec<ProcessMetrics>,
+    /// Automatic restarts in the current supervisor streak.
+    pub restart_count: u32,
+    /// Time since the sidecar last passed its health check.
+    pub healthy_for_secs: Option<u64>,
+    /// Milliseconds since UNIX epoch (UTC).
+    pub timestamp: u64,
+}
+
+/// Raw counters read from the OS for one PID.
+struct Sample {
+    cpu_secs: f64,
+    rss_bytes: u64,
+    open_files: Option<u64>,
+    uptime_secs: u64,
+}
+
+/// Previous CPU time per PID, for turning cumulative CPU time into a rate.
+static LAST_CPU: Mutex<Option<HashMap<u32, (Instant, f64)>>> = Mutex::new(None);
+
+// ── Public API ──────────────────────────────────────────────────────────
+
+/// Sample every process of the OpenCode tree.
+pub async fn collect(state: &SharedOpenCodeState) -> OpenCodeMetrics {
+    let (targets, restart_count, healthy_for_secs) = {
+        let s = state.lock().await;
+        let targets: Vec<(ProcessRole, u32)> = [
+            (ProcessRole::Sidecar, s.child.as_ref().map(|c| c.pid())),
+            (ProcessRole::Launcher, s.launcher_pid),
+            (ProcessRole::McpServer, s.mcp_server_pid),
+            (ProcessRole::Rojo, s.rojo.pid()),
+        ]
+        .into_iter()
+        .filter_map(|(role, pid)| pid.map(|pid| (role, pid)))
+        .collect();
+        (
+            targets,
+            s.restart_count,
+            s.running_since.map(|since| since.elapsed().as_secs()),
+        )
+    };
+
+    let processes = tauri::async_runtime::spawn_blocking(move || {
+        let sampled: Vec<u32> = targets.iter().map(|&(_, pid)| pid).collect();
+        let processes = targets
+            .into_iter()
+            .filter_map(|(role, pid)| {
+                let sample = sample(pid)?;
+                Some(ProcessMetrics {
+                    role,
+                    pid,
+                    cpu_percent: cpu_percent(pid, &sample),
+                    rss_bytes: sample.rss_bytes,
+                    open_files: sample.open_files,
+                    uptime_secs: sample.uptime_secs,
+                })
+            })
+            .collect();
+        forget_cpu_samples_except(&sampled);
+        processes
+    })
+    .await
+    .unwrap_or_default();
+
+    OpenCodeMetrics {
+        processes,
+        restart_count,
+        healthy_for_secs,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+    }
+}
+
+/// Push metrics to the debug-logs window while it is open. Call once from
+/// `setup`.
+pub fn spawn_emitter(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<SharedOpenCodeState>().inner().clone();
+        loop {
+            tokio::time::sleep(EMIT_INTERVAL).await;
+            if app.get_webview_window(DEBUG_LOGS_LABEL).is_none() {
+                continue;
+            }
+            let metrics = collect(&state).await;
+            let _ = app.emit_to(DEBUG_LOGS_LABEL, "opencode-metrics", &metrics);
+        }
+    });
+}
+
+// ── Sampling ────────────────────────────────────────────────────────────
+
+/// CPU usage since the previous sample of `pid`, in percent of one core.
+fn cpu_percent(pid: u32, sample: &Sample) -> f64 {
+    let now = Instant::now();
+    let mut guard = LAST_CPU.lock().unwrap_or_else(|e| e.into_inner());
+    let last = guard.get_or_insert_with(HashMap::new);
+    let percent = match last.get(&pid) {
+        Some(&(at, cpu_secs)) if sample.cpu_secs >= cpu_secs => {
+            let wall = now.duration_since(at).as_secs_f64();
+            if wall > 0.0 {
+                (sample.cpu_secs - cpu_secs) / wall * 100.0
+            } else {
+                0.0
+            }
+        }
+        _ if sample.uptime_secs > 0 => sample.cpu_secs / sample.uptime_secs as f64 * 100.0,
+        _ => 0.0,
+    };
+    last.insert(pid, (now, sample.cpu_secs));
+    percent
+}
+
+/// Drop the CPU baselines of PIDs that are no longer part of the tree, so
+/// PIDs from earlier restarts don't pile up.
+fn forget_cpu_samples_except(pids: &[u32]) {
+    let mut guard = LAST_CPU.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(last) = guard.as_mut() {
+        last.retain(|pid, _| pids.contains(pid));
+    }
+}
+
+/// Read the counters for `pid`, or `None` if the process is gone.
+fn sample(pid: u32) -> Option<Sample> {
+    #[cfg(unix)]
+    {
+        let out = std::process::Command::new("ps")
+            .args(["-o", "time=,etime=,rss=", "-p", &pid.to_string()])
+            .output()
+            .ok()?;
+        if !out.status.success() {
+            return None;
+        }
+        let text = String::from_utf8_lossy(&out.stdout);
+        let mut cols = text.split_whitespace();
+        let cpu_secs = parse_ps_duration(cols.next()?)?;
+        let uptime_secs = parse_ps_duration(cols.next()?)? as u64;
+        let rss_kib: u64 = cols.next()?.parse().ok()?;
+        Some(Sample {
+            cpu_secs,
+            rss_bytes: rss_kib * 1024,
+            open_files: open_files(pid),
+            uptime_secs,
+        })
+    }
+
+    #[cfg(windows)]
+    {
+        let script = format!(
+            "$p = Get-Process -Id {pid} -ErrorAction SilentlyContinue; \
+             if ($p) {{ [long]$p.TotalProcessorTime.TotalMilliseconds; $p.WorkingSet64; \
+             $p.HandleCount; [long]((Get-Date) - $p.StartTime).TotalSeconds }}"
+        );
+        let out = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&out.stdout);
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+        let cpu_ms: u64 = lines.next()?.parse().ok()?;
+        let rss_bytes = lines.next()?.parse().ok()?;
+        let open_files = lines.next().and_then(|l| l.parse().ok());
+        let uptime_secs = lines.next().and_then(|l| l.parse().ok()).unwrap_or(0);
+        Some(Sample {
+            cpu_secs: cpu_ms as f64 / 1000.0,
+            rss_bytes,
+            open_files,
+            uptime_secs,
+        })
+    }
+}
+
+/// Parse `ps` time columns: `[[DD-]HH:]MM:SS[.cc]`.
+#[cfg(unix)]
+fn parse_ps_duration(text: &str) -> Option<f64> {
+    let (days, rest) = match text.split_once('-') {
+        Some((d, rest)) => (d.parse::<f64>().ok()?, rest),
+        None => (0.0, text),
+    };
+    let mut secs = 0.0;
+    for part in rest.split(':') {
+        secs = secs * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(days * 86_400.0 + secs)
+}
+
+#[cfg(target_os = "linux")]
+fn open_files(pid: u32) -> Option<u64> {
+    std::fs::read_dir(format!("/proc/{pid}/fd"))
+        .ok()
+        .map(|entries| entries.count() as u64)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn open_files(pid: u32) -> Option<u64> {
+    let out = std::process::Command::new("lsof")
+        .args(["-n", "-P", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    // First line is the column header.
+    let lines = String::from_utf8_lossy(&out.stdout).lines().count() as u64;
+    Some(lines.saturating_sub(1))
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+/// CPU, memory, open files and uptime of the sidecar, the MCP launcher
+/// and the MCP server, plus the current restart count.
+#[tauri::command]
+pub async fn get_opencode_metrics(
+    state: tauri::State<'_, SharedOpenCodeState>,
+) -> Result<OpenCodeMetrics, String> {
+    Ok(collect(state.inner()).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn sample(cpu_secs: f64, uptime_secs: u64) -> Sample {
+        Sample {
+            cpu_secs,
+            rss_bytes: 0,
+            open_files: None,
+            uptime_secs,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ps_durations_parse_in_every_form() {
+        assert_eq!(parse_ps_duration("00:07"), Some(7.0));
+        assert_eq!(parse_ps_duration("12:34"), Some(754.0));
+        assert_eq!(parse_ps_duration("01:02:03"), Some(3723.0));
+        assert_eq!(
+            parse_ps_duration("2-01:02:03"),
+            Some(2.0 * 86_400.0 + 3723.0)
+        );
+        assert_eq!(parse_ps_duration("0:01.50"), Some(1.5));
+        assert_eq!(parse_ps_duration("-"), None);
+        assert_eq!(parse_ps_duration("1:xx"), None);
+    }
+
+    #[test]
+    fn first_cpu_sample_is_the_lifetime_average() {
+        assert_eq!(cpu_percent(u32::MAX - 1, &sample(5.0, 20)), 25.0);
+        assert_eq!(cpu_percent(u32::MAX - 3, &sample(5.0, 0)), 0.0);
+    }
+
+    #[test]
+    fn later_cpu_samples_use_the_time_since_the_previous_one() {
+        let pid = u32::MAX - 2;
+        let at = Instant::now() - Duration::from_secs(2);
+        LAST_CPU
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .insert(pid, (at, 10.0));
+        let percent = cpu_percent(pid, &sample(11.0, 100));
+        assert!((45.0..=50.0).contains(&percent), "{percent}");
+
+        // A lower CPU time means the PID was reused; start over.
+        assert_eq!(cpu_percent(pid, &sample(1.0, 4)), 25.0);
+    }
+}
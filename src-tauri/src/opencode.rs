@@ -9,21 +9,14 @@
 //! process management (including hiding console windows on Windows).
 
 use std::sync::{Arc, OnceLock};
-use tauri::{AppHandle, Emitter};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Runtime};
 use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 use tauri_plugin_shell::ShellExt;
 use tokio::sync::Mutex;
 
-/// BloxBot's reserved port ranges within the IANA dynamic/private range
-/// (49152-65535). Each block is 10 ports; the app binds to the first
-/// available port in each block.
-///
-/// 59200-59209: OpenCode server (HTTP API)
-/// 59210-59219: MCP bridge (Studio plugin ↔ MCP server)
-/// 59220-59229: MCP launcher control endpoint
-const OC_PORT_START: u16 = 59200;
-const MCP_PORT_START: u16 = 59210;
-const PORT_RANGE: u16 = 10;
+use crate::ports::PortRanges;
+use crate::process_registry::ProcessRole;
 
 /// All servers bind to IPv4 loopback. Using `"localhost"` is **not**
 /// safe because macOS resolves it to `[::1]` (IPv6), causing our IPv4
@@ -48,14 +41,94 @@ pub enum OpenCodeStatus {
     Stopped,
     Starting,
     Running,
+    /// The process is alive but failing liveness probes. The watchdog
+    /// restarts it if it doesn't recover.
+    Degraded,
+    /// The sidecar crashed and the supervisor is waiting to restart it.
+    Restarting,
     Error(String),
 }
 
+/// The step `do_start` is currently executing. Emitted alongside
+/// `Starting` so the loading screen can show progress, and kept after a
+/// failure so it's clear *where* startup broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum StartupPhase {
+    ResolvingNode,
+    CleaningStaleProcesses,
+    AllocatingPorts,
+    WritingConfig,
+    Spawning,
+    WaitingForHealth,
+    McpConnecting,
+}
+
+/// Machine-readable cause of a startup failure, so the frontend can offer
+/// targeted remediation instead of parsing the error text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum StartupErrorCode {
+    /// The bundled Node.js runtime could not be found.
+    NodeMissing,
+    /// The bundled MCP launcher (`dist/launcher.js`) could not be found.
+    LauncherMissing,
+    /// No free port in one of the reserved ranges.
+    PortExhausted,
+    /// Another process bound a port between its release and the server
+    /// binding it, on every attempt.
+    PortTaken,
+    /// Creating directories or writing `opencode.json` failed.
+    ConfigWriteFailed,
+    /// The sidecar binary could not be spawned.
+    SpawnFailed,
+    /// The sidecar is alive but never answered its health check.
+    HealthTimeout,
+    /// The sidecar exited before becoming healthy.
+    EarlyExit,
+    /// The server crashed `max_attempts` times in a row and automatic
+    /// restarts gave up.
+    TooManyCrashes,
+    /// Anything else (e.g. the home directory can't be resolved).
+    Internal,
+}
+
+/// A startup failure: the user-facing message plus its error code.
+#[derive(Debug, Clone)]
+pub struct StartupError {
+    pub code: StartupErrorCode,
+    pub message: String,
+}
+
+impl StartupError {
+    fn new(code: StartupErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for StartupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
 /// Payload emitted with the `opencode-status-changed` event.
 #[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct StatusPayload {
     pub status: OpenCodeStatus,
     pub port: u16,
+    /// Automatic restarts attempted since the server was last stable.
+    pub restart_count: u32,
+    /// Configured maximum, so the UI can render "restarting (2/5)".
+    pub max_restarts: u32,
+    /// Raw exit details of the most recent unexpected termination.
+    pub last_exit_reason: Option<String>,
+    /// Current (or, after a failure, last attempted) startup phase.
+    pub phase: Option<StartupPhase>,
+    /// Set together with `OpenCodeStatus::Error` when startup failed.
+    pub error_code: Option<StartupErrorCode>,
 }
 
 // ── State ───────────────────────────────────────────────────────────────
@@ -64,7 +137,53 @@ pub struct OpenCodeState {
     pub status: OpenCodeStatus,
     pub port: u16,
     pub mcp_port: u16,
+    /// Launcher control endpoint, allocated alongside `mcp_port` and
+    /// passed to the launcher as `BLOXBOT_CONTROL_PORT`.
+    pub control_port: u16,
     pub(crate) child: Option<CommandChild>,
+    /// Incremented every time a child is spawned or intentionally torn
+    /// down. Event handlers compare it against the generation they were
+    /// started with so a stale `Terminated` event can't clobber a newer
+    /// process.
+    pub(crate) child_generation: u64,
+    /// When the current child last passed its health check.
+    pub(crate) running_since: Option<Instant>,
+    /// When the current child last printed a (non-noise) line. Extends
+    /// the startup health deadline while the sidecar is making progress.
+    pub(crate) last_output_at: Option<Instant>,
+    /// Ports and environment the current child was spawned with.
+    pub(crate) spawn_signature: Option<SpawnSignature>,
+    /// PIDs reported by the launcher's control endpoint. OpenCode spawns
+    /// these itself, so they are only known once the MCP server is up.
+    pub launcher_pid: Option<u32>,
+    pub mcp_server_pid: Option<u32>,
+    pub restart_count: u32,
+    pub last_exit_reason: Option<String>,
+    pub phase: Option<StartupPhase>,
+    pub error_code: Option<StartupErrorCode>,
+    /// Set once by the shutdown coordinator; blocks any further starts
+    /// (supervisor restarts, retry button) while the app exits.
+    pub(crate) shutting_down: bool,
+    /// The managed `rojo serve` process, which lives and dies with the
+    /// sidecar.
+    pub rojo: crate::rojo::RojoState,
+    /// Set while `reconfigure_when_idle` waits for the sessions to finish,
+    /// so repeated requests share one reload.
+    pub(crate) reload_pending: bool,
+    /// Name of the place open in the connected Studio, looked up when the
+    /// plugin connects and every `PLACE_RECHECK_INTERVAL` after that.
+    /// `None` while no plugin is connected.
+    pub studio_place: Option<String>,
+    /// Set while that lookup is in flight.
+    pub(crate) place_lookup_running: bool,
+    /// No new lookup starts before this.
+    pub(crate) next_place_lookup: Option<Instant>,
+    /// Lookups that failed in a row, for the retry backoff.
+    pub(crate) place_lookup_failures: u32,
+    /// Studio place name the current `opencode.json` was rendered with.
+    pub(crate) prompt_place: Option<String>,
+    /// Plugins last made ready in the cache, reused by `reconfigure`.
+    pub(crate) prepared_plugins: Option<crate::plugins::Prepared>,
 }
 
 impl Default for OpenCodeState {
@@ -73,7 +192,27 @@ impl Default for OpenCodeState {
             status: OpenCodeStatus::Stopped,
             port: 0,
             mcp_port: 0,
+            control_port: 0,
             child: None,
+            child_generation: 0,
+            running_since: None,
+            last_output_at: None,
+            spawn_signature: None,
+            launcher_pid: None,
+            mcp_server_pid: None,
+            restart_count: 0,
+            last_exit_reason: None,
+            phase: None,
+            error_code: None,
+            shutting_down: false,
+            rojo: crate::rojo::RojoState::default(),
+            reload_pending: false,
+            studio_place: None,
+            place_lookup_running: false,
+            next_place_lookup: None,
+            place_lookup_failures: 0,
+            prompt_place: None,
+            prepared_plugins: None,
         }
     }
 }
@@ -83,42 +222,50 @@ pub type SharedOpenCodeState = Arc<Mutex<OpenCodeState>>;
 // ── Helpers ─────────────────────────────────────────────────────────────
 
 /// Emit a status change event to the frontend.
-fn emit_status(app: &AppHandle, status: &OpenCodeStatus, port: u16) {
+fn emit_status<R: Runtime>(app: &AppHandle<R>, s: &OpenCodeState) {
     let _ = app.emit(
         "opencode-status-changed",
         StatusPayload {
-            status: status.clone(),
-            port,
+            status: s.status.clone(),
+            port: s.port,
+            restart_count: s.restart_count,
+            max_restarts: crate::config::get().restart_policy.max_attempts,
+            last_exit_reason: s.last_exit_reason.clone(),
+            phase: s.phase,
+            error_code: s.error_code,
         },
     );
 }
 
 /// Update the state and emit the event in one step.
 async fn set_status(state: &SharedOpenCodeState, app: &AppHandle, status: OpenCodeStatus) {
-    let port;
-    {
-        let mut s = state.lock().await;
-        s.status = status.clone();
-        port = s.port;
+    let mut s = state.lock().await;
+    if let OpenCodeStatus::Running = status {
+        s.running_since = Some(Instant::now());
+        s.phase = None;
     }
-    emit_status(app, &status, port);
+    // A code only describes the `Error` status it was reported with.
+    if !matches!(status, OpenCodeStatus::Error(_)) {
+        s.error_code = None;
+    }
+    s.status = status;
+    emit_status(app, &s);
 }
 
-/// Find the first available TCP port starting from `start`, trying
-/// up to `PORT_RANGE` consecutive ports. All servers bind to `LOOPBACK`
-/// (127.0.0.1), so we only need to probe that address.
-async fn find_available_port(start: u16) -> u16 {
-    for port in start..start.saturating_add(PORT_RANGE) {
-        if tokio::net::TcpListener::bind((LOOPBACK, port))
-            .await
-            .is_ok()
-        {
-            return port;
-        }
-        log::debug!("Port {port} unavailable, skipping");
-    }
-    log::error!("All ports {start}-{} are unavailable!", start.saturating_add(PORT_RANGE - 1));
-    start // fallback — let the spawn surface the real error
+/// Advance to the next startup phase and emit it.
+async fn set_phase(state: &SharedOpenCodeState, app: &AppHandle, phase: StartupPhase) {
+    log::info!("Startup phase: {phase:?}");
+    let mut s = state.lock().await;
+    s.phase = Some(phase);
+    emit_status(app, &s);
+}
+
+/// Record a startup failure and emit it as an `Error` status.
+async fn set_startup_error(state: &SharedOpenCodeState, app: &AppHandle, err: &StartupError) {
+    let mut s = state.lock().await;
+    s.status = OpenCodeStatus::Error(err.message.clone());
+    s.error_code = Some(err.code);
+    emit_status(app, &s);
 }
 
 /// Strip the Windows extended-length path prefix (`\\?\`) from a path string.
@@ -132,49 +279,85 @@ fn strip_win_prefix(p: &std::path::Path) -> String {
 
 // ── Startup cleanup ─────────────────────────────────────────────────────
 
-/// Kill any stale processes listening on our reserved port ranges
-/// (59200-59229). This handles the case where BloxBot crashed or was
-/// force-quit, leaving orphan processes holding ports.
+/// Kill stale processes left behind by a previous BloxBot run that crashed
+/// or was force-quit, so their ports in our reserved ranges are free again.
 ///
-/// Covers all three ranges:
-/// - 59200-59209: OpenCode server
-/// - 59210-59219: MCP bridge
-/// - 59220-59229: Launcher control endpoint
+/// Only processes recorded in the `process_registry` whose start time and
+/// command line still match are terminated. Anything else listening on
+/// our ports — an unrelated program, or the processes of another BloxBot
+/// instance that is still running — is logged and left alone.
 ///
-/// Uses platform-specific commands:
-/// - macOS/Linux: `lsof -ti tcp:PORT` to find PIDs, then `kill -9`
-/// - Windows: `netstat -ano` to find PIDs, then `taskkill /F /PID`
+/// Covers all three configured ranges (by default 59200-59229, see
+/// `config::PortConfig`).
+///
+/// Owned processes that ended up on an ephemeral port are found through
+/// the registry alone.
 pub fn cleanup_stale_processes() {
-    let start = OC_PORT_START; // 59200
-    let end = OC_PORT_START + PORT_RANGE * 3; // 59230 (covers 59200-59229)
-    log::info!("Checking for stale processes on ports {start}-{}", end - 1);
+    let ranges = PortRanges::configured();
+    let ports: Vec<u16> = ranges.all().collect();
+    log::info!(
+        "Checking for stale processes on ports {}, {} and {} (+{} each)",
+        ranges.opencode_start,
+        ranges.bridge_start,
+        ranges.control_start,
+        ranges.size - 1
+    );
+
+    let ownership = crate::process_registry::verify_owned();
+    if let Some(owner) = ownership.live_owner {
+        log::warn!(
+            "Recorded processes belong to a running BloxBot instance (PID {owner}); not touching them"
+        );
+    }
+
+    for (port, pid) in listening_pids(&ports) {
+        if pid == std::process::id() || ownership.owned.iter().any(|p| p.pid == pid) {
+            continue;
+        }
+        let command = crate::process_registry::process_info(pid)
+            .map(|i| i.command)
+            .unwrap_or_else(|| "unknown command".to_string());
+        log::warn!(
+            "Port {port} is held by foreign process PID {pid} ({command}); leaving it alone"
+        );
+    }
+
+    for p in &ownership.owned {
+        log::info!("Killing stale {:?} process PID {}", p.role, p.pid);
+        crate::process_registry::terminate(p.pid);
+        crate::process_registry::forget(p.pid);
+    }
+    if ownership.owned.is_empty() {
+        log::info!("No stale processes found");
+    } else {
+        log::info!("Killed {} stale process(es)", ownership.owned.len());
+    }
+}
+
+/// List `(port, pid)` pairs for processes listening on any of `ports`.
+///
+/// Uses platform-specific commands:
+/// - macOS/Linux: `lsof -ti tcp:PORT -sTCP:LISTEN`
+/// - Windows: `netstat -ano`
+fn listening_pids(ports: &[u16]) -> Vec<(u16, u32)> {
+    let mut found = Vec::new();
 
     #[cfg(unix)]
     {
-        let mut killed = 0u32;
-        for port in start..end {
+        for &port in ports {
             let output = std::process::Command::new("lsof")
-                .args(["-ti", &format!("tcp:{port}")])
+                .args(["-ti", &format!("tcp:{port}"), "-sTCP:LISTEN"])
                 .output();
 
             if let Ok(out) = output {
                 let pids = String::from_utf8_lossy(&out.stdout);
                 for pid_str in pids.split_whitespace() {
                     if let Ok(pid) = pid_str.trim().parse::<u32>() {
-                        log::info!("Killing stale process PID {pid} on port {port}");
-                        let _ = std::process::Command::new("kill")
-                            .args(["-9", &pid.to_string()])
-                            .output();
-                        killed += 1;
+                        found.push((port, pid));
                     }
                 }
             }
         }
-        if killed > 0 {
-            log::info!("Killed {killed} stale process(es)");
-        } else {
-            log::info!("No stale processes found");
-        }
     }
 
     #[cfg(windows)]
@@ -186,7 +369,7 @@ pub fn cleanup_stale_processes() {
 
         if let Ok(out) = output {
             let text = String::from_utf8_lossy(&out.stdout);
-            for port in start..end {
+            for &port in ports {
                 let needle = format!("{}:{}", LOOPBACK, port);
                 for line in text.lines() {
                     if line.contains(&needle) && line.contains("LISTENING") {
@@ -194,10 +377,7 @@ pub fn cleanup_stale_processes() {
                         if let Some(pid_str) = line.split_whitespace().last() {
                             if let Ok(pid) = pid_str.parse::<u32>() {
                                 if pid > 0 {
-                                    log::info!("Killing stale process PID {pid} on port {port}");
-                                    let _ = std::process::Command::new("taskkill")
-                                        .args(["/F", "/PID", &pid.to_string()])
-                                        .output();
+                                    found.push((port, pid));
                                 }
                             }
                         }
@@ -206,244 +386,82 @@ pub fn cleanup_stale_processes() {
             }
         }
     }
-}
-
-// ── Core lifecycle ──────────────────────────────────────────────────────
 
-/// Start the OpenCode server. Called automatically on app launch.
-///
-/// Uses `tauri-plugin-shell` sidecar support to spawn the bundled
-/// `opencode` binary. stdout/stderr and process exit are handled via
-/// the shell plugin's event channel, replacing manual BufReader capture
-/// and polling-based exit monitoring.
-pub async fn start_opencode_server(
-    state: SharedOpenCodeState,
-    app: AppHandle,
-) -> Result<u16, String> {
-    // Guard: don't double-start
-    {
-        let current = state.lock().await;
-        if matches!(
-            current.status,
-            OpenCodeStatus::Running | OpenCodeStatus::Starting
-        ) {
-            return Ok(current.port);
-        }
-    }
-
-    let nodejs_bin_dir = match crate::paths::bundled_nodejs_bin_dir() {
-        Ok(dir) => dir,
-        Err(e) => {
-            log::error!("Failed to find Node.js: {e}");
-            set_status(&state, &app, OpenCodeStatus::Error(e.clone())).await;
-            return Err(e);
-        }
-    };
-    log::info!("Node.js bin: {}", nodejs_bin_dir.display());
+    found
+}
 
-    set_status(&state, &app, OpenCodeStatus::Starting).await;
+// ── Sidecar environment ─────────────────────────────────────────────────
 
-    // Run the actual startup logic. If anything fails after this point,
-    // transition status to Error so the frontend can show a retry button
-    // instead of being stuck on "Starting up..." forever.
-    match do_start(&state, &app, &nodejs_bin_dir).await {
-        Ok(port) => Ok(port),
-        Err(e) => {
-            set_status(&state, &app, OpenCodeStatus::Error(e.clone())).await;
-            Err(e)
-        }
-    }
+/// The bundled runtimes the sidecar and the MCP launcher need.
+struct Runtimes {
+    nodejs_bin_dir: std::path::PathBuf,
+    /// Launcher entry script, with the Windows `\\?\` prefix stripped.
+    mcp_entry: String,
 }
 
-/// Inner startup logic extracted so that any `?` failure is caught by the
-/// caller and translated into an `Error` status transition.
-async fn do_start(
-    state: &SharedOpenCodeState,
-    app: &AppHandle,
-    nodejs_bin_dir: &std::path::Path,
-) -> Result<u16, String> {
-    // Kill any stale processes from a previous crash/force-quit before
-    // probing ports. This ensures find_available_port gets clean ports.
-    cleanup_stale_processes();
-    // Brief pause so the OS can release the TCP sockets after killing processes.
-    tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-
-    let port = find_available_port(OC_PORT_START).await;
-    let mcp_port = find_available_port(MCP_PORT_START).await;
-    let control_port = mcp_port.wrapping_add(10); // 59220+ range
-    log::info!("OpenCode port: {port}, MCP bridge port: {mcp_port}, control port: {control_port}");
+fn resolve_runtimes() -> Result<Runtimes, StartupError> {
+    use StartupErrorCode as Code;
 
-    {
-        let mut s = state.lock().await;
-        s.port = port;
-        s.mcp_port = mcp_port;
-    }
+    let nodejs_bin_dir = crate::paths::bundled_nodejs_bin_dir()
+        .map_err(|e| StartupError::new(Code::NodeMissing, e))?;
+    log::info!("Node.js bin: {}", nodejs_bin_dir.display());
 
     // Configure the MCP server to use our bundled copy (run directly with node).
     // This avoids npx download issues on Windows and ensures a known-good version.
-    let launcher_dir = crate::paths::bundled_launcher_dir().map_err(|e| {
-        log::error!("Failed to find MCP launcher: {e}");
-        e
-    })?;
+    let launcher_dir = crate::paths::bundled_launcher_dir()
+        .map_err(|e| StartupError::new(Code::LauncherMissing, e))?;
     let mcp_entry = launcher_dir.join("dist").join("launcher.js");
     log::info!("MCP launcher: {}", mcp_entry.display());
 
-    #[cfg(unix)]
-    let node_cmd = "node";
-    #[cfg(windows)]
-    let node_cmd = "node.exe";
-
     // On Windows, std::env::current_exe() runs fs::canonicalize which
     // prepends \\?\ to the path. We already strip this for PATH, but the
     // MCP entry path also needs it stripped — Node.js module resolution
     // can break when the entry script path has this prefix.
     #[cfg(unix)]
-    let mcp_entry_str = mcp_entry.to_string_lossy().to_string();
+    let mcp_entry = mcp_entry.to_string_lossy().to_string();
     #[cfg(windows)]
-    let mcp_entry_str = strip_win_prefix(&mcp_entry);
-
-    // mcp_port and control_port are already set above from the reserved range.
-
-    let mcp_config = serde_json::json!({
-        "plugin": [
-            "opencode-gemini-auth@latest"
-        ],
-        "mcp": {
-            "roblox-studio": {
-                "type": "local",
-                "command": [node_cmd, &mcp_entry_str],
-                "enabled": true,
-                "environment": {
-                    "ROBLOX_STUDIO_HOST": LOOPBACK,
-                    "ROBLOX_STUDIO_PORT": mcp_port.to_string(),
-                    "BLOXBOT_CONTROL_PORT": control_port.to_string()
-                }
-            }
-        },
-        "default_agent": "studio",
-        "agent": {
-            "build": {
-                "description": "Executes tools based on the conversation"
-            },
-            "studio": {
-                "mode": "primary",
-                "description": "Roblox Studio development assistant",
-                "prompt": concat!(
-                    "You are BloxBot, an expert Roblox game developer working directly inside Roblox Studio. ",
-                    "You have deep knowledge of the Roblox engine, the DataModel, Luau, and Studio workflows. ",
-                    "You build games by using MCP tools to modify the live Studio session — not by showing code snippets.\n\n",
-
-                    // ── Workflow ──────────────────────────────────────────
-                    "## Workflow\n",
-                    "1. **Explore first.** Before modifying anything, understand the project: `get_project_structure` (use maxDepth 5-10), `get_services`, `get_instance_children`, `get_selection`. Never guess at paths. Read existing scripts to understand conventions before writing new code.\n",
-                    "2. **Make changes with tools.** Always use the MCP tools to create instances, set properties, write scripts, etc. directly in Studio. Never tell the user to paste code.\n",
-                    "3. **Verify.** After changes, read back the result (`get_script_source`, `get_instance_properties`) to confirm correctness.\n",
-                    "4. **Debug with playtests.** When behavior must be verified at runtime: instrument with print/warn, `start_playtest`, ask the user to perform actions, poll output with `get_playtest_output`, probe live state with `execute_luau`, `stop_playtest`, fix, repeat.\n\n",
-
-                    // ── Project awareness ─────────────────────────────────
-                    "## Project Awareness\n",
-                    "At the start of a session or when you encounter an unfamiliar project, **scan the codebase** to learn its architecture. Use `get_project_structure` with high depth, then read key scripts. Identify:\n",
-                    "- **Frameworks**: Knit, AeroGameFramework, Rojo project structure, Nevermore, Fusion, Roact/React-lua, Rodux, ProfileService/ProfileStore, DataStore2, etc. If the project uses one, all new code must follow its patterns (e.g. Knit Services/Controllers, Roact components, Fusion scopes).\n",
-                    "- **Folder conventions**: How are scripts organized? Is there a Shared/ folder, a Systems/ folder, a Components/ folder? Place new code where it belongs.\n",
-                    "- **Module patterns**: How does existing code structure ModuleScripts? (return table, OOP class via metatables, functional). Match the style.\n",
-                    "- **Communication patterns**: Does the project use RemoteEvents directly, or wrap them (e.g. Knit, BridgeNet2, Red)? Use the same approach.\n",
-                    "- **Naming conventions**: Do existing scripts use PascalCase, camelCase, or a prefix system? Does the project use specific naming for remotes, modules, etc.?\n\n",
-                    "**Carry this context throughout the session.** Every script you write or edit must be consistent with the project's existing patterns. Do not introduce a new framework or architectural style unless the user explicitly asks for a refactor.\n\n",
-
-                    // ── Tool guidance ─────────────────────────────────────
-                    "## Tool Guide\n\n",
-
-                    "**Scripts** — Always read first with `get_script_source` (returns numbered lines via `numberedSource`). ",
-                    "For partial edits use `edit_script_lines`/`insert_script_lines`/`delete_script_lines` — they are safer and faster than rewriting the whole source. ",
-                    "Only use `set_script_source` for new scripts or full rewrites. Line numbers are 1-indexed and inclusive.\n\n",
-
-                    "**Instances** — Use `create_object_with_properties` to create and configure in one call. ",
-                    "Use `mass_create_objects_with_properties` when creating multiple instances. ",
-                    "Use `smart_duplicate` with positionOffset/propertyVariations for grids and arrays of objects.\n\n",
-
-                    "**Properties** — `set_property` for single changes. `mass_set_property` for bulk. ",
-                    "`set_relative_property` to offset from the current value (e.g. move +5 on Y). ",
-                    "`set_calculated_property` for formula-driven values across multiple instances.\n\n",
-
-                    "**Attributes & Tags** — Use attributes for custom data on instances (health, cost, team). ",
-                    "Use CollectionService tags to group instances for system-level behavior (\"Lava\", \"Interactable\").\n\n",
-
-                    "**Execute Luau** — `execute_luau` runs Luau in the plugin context with access to `game`, all services, and `print()`. ",
-                    "Use it for complex queries, batch operations, or anything the focused tools don't cover.\n\n",
-
-                    "**Playtest & Live Debugging** — `start_playtest` (mode: \"play\" or \"run\"), `get_playtest_output` to poll logs, `stop_playtest` to end. ",
-                    "This is your debugger. Use it proactively when the user reports bugs or when you need to verify runtime behavior. ",
-                    "Combine all three approaches for maximum effectiveness:\n",
-                    "  1. **Instrumented logging** — Add strategic print/warn statements before the playtest to trace execution flow and variable state.\n",
-                    "  2. **Live probing with `execute_luau`** — While the playtest is running, use `execute_luau` to inspect live game state: query property values, read attributes, check player positions, verify instance existence, evaluate conditions. This lets you diagnose issues without stopping the session.\n",
-                    "  3. **User-directed actions** — Ask the user to perform specific in-game actions during the playtest (\"walk to the red part\", \"click the shop button\", \"try jumping on the platform\") then immediately poll output and probe state to observe the result. This is essential for testing interactions, UI flows, physics, and any player-triggered behavior.\n",
-                    "The full debug loop: instrument code → start playtest → ask user to trigger the behavior → poll output + probe values with execute_luau → stop → analyze → fix → repeat.\n\n",
-
-                    // ── Roblox architecture ───────────────────────────────
-                    "## Roblox Architecture\n\n",
-
-                    "**DataModel hierarchy**: game (DataModel) → Services → Instances. Key services and their roles:\n",
-                    "- `Workspace` — 3D world. BaseParts, Models, Terrain, Camera live here. Replicated.\n",
-                    "- `ServerScriptService` — Server Scripts. Never accessible from client.\n",
-                    "- `ServerStorage` — Server-only assets, data templates. Not replicated to clients.\n",
-                    "- `ReplicatedStorage` — Shared between server and client. ModuleScripts, RemoteEvents, RemoteFunctions, assets.\n",
-                    "- `StarterPlayerScripts` / `StarterCharacterScripts` — LocalScripts cloned to each player.\n",
-                    "- `StarterGui` — ScreenGuis/LocalScripts cloned to each player's PlayerGui.\n",
-                    "- `Players` — Player objects (with Character models in Workspace).\n",
-                    "- `Lighting` — Atmosphere, sky, time of day, post-processing.\n",
-                    "- `SoundService` — Ambient and spatial audio.\n",
-                    "- `TweenService`, `RunService`, `UserInputService`, `ContextActionService`, `CollectionService`, `PhysicsService`, `MarketplaceService`, `DataStoreService`, `MessagingService`, `HttpService` — use `:GetService()` to access.\n\n",
-
-                    "**Client-server model**: Server is authoritative. Clients see a replicated subset. Communication via RemoteEvents (fire-and-forget) and RemoteFunctions (request-response) in ReplicatedStorage. ",
-                    "**Never trust the client.** Validate all inputs server-side. Exploiters can fire any RemoteEvent with any arguments.\n\n",
-
-                    "**Script types**:\n",
-                    "- `Script` — runs on server (ServerScriptService, Workspace, or ServerStorage). Has `game:GetService()` access to all server APIs.\n",
-                    "- `LocalScript` — runs on client (StarterPlayerScripts, StarterCharacterScripts, StarterGui). Has access to `LocalPlayer`, UserInputService, Camera.\n",
-                    "- `ModuleScript` — shared code loaded via `require()`. Place in ReplicatedStorage (shared), ServerStorage (server-only), or alongside consumers.\n\n",
-
-                    // ── Luau style ────────────────────────────────────────
-                    "## Luau Style\n",
-                    "- Write idiomatic **Luau**. Use type annotations, `if-then-else` expressions, string interpolation (`backtick syntax`), and typed `for` loops.\n",
-                    "- **Descriptive names only.** `player` not `p`, `character` not `char`, `humanoid` not `hum`, `connection` not `conn`. Readability over brevity, always.\n",
-                    "- PascalCase for services, instances, properties, methods. camelCase for local variables and functions.\n",
-                    "- Use `:GetService()` to access services. Use `:WaitForChild()` on the client when referencing instances that may not have replicated yet.\n",
-                    "- Handle cleanup: disconnect connections, destroy cloned instances, use `Maid`/`Trove` patterns or `task.cancel()` for spawned threads.\n",
-                    "- Use `task.spawn`, `task.defer`, `task.delay`, `task.wait` (not legacy `spawn`, `wait`, `delay`).\n\n",
-
-                    // ── Knowledge & docs ──────────────────────────────────
-                    "## Roblox Knowledge\n",
-                    "You have deep knowledge of the Roblox engine, but APIs evolve. ",
-                    "When uncertain about a class, property, method, or enum — or when using less-common APIs — ",
-                    "**search the Roblox documentation** (create.roblox.com/docs) or the DevForum (devforum.roblox.com) before writing code. ",
-                    "Do not guess API signatures. Getting a method name or parameter wrong wastes the user's time.\n\n",
-
-                    "Common reference points:\n",
-                    "- Instance API: Instance.new(), :Clone(), :Destroy(), :FindFirstChild(), :FindFirstChildOfClass(), :GetChildren(), :GetDescendants(), :WaitForChild(), :SetAttribute(), :GetAttribute()\n",
-                    "- Events: .Changed, :GetPropertyChangedSignal(), .ChildAdded, .ChildRemoved, .Touched, .PlayerAdded, .CharacterAdded\n",
-                    "- Physics: BasePart.Anchored, AssemblyLinearVelocity, CollisionGroup, CustomPhysicalProperties\n",
-                    "- UI: ScreenGui, Frame, TextLabel, TextButton, ImageLabel, UIListLayout, UIStroke, UICorner, UIGradient, UIPadding\n\n",
-
-                    // ── Communication ─────────────────────────────────────
-                    "## Communication\n",
-                    "Be concise and practical. Show what you did, not how to do it — the tools already did it. ",
-                    "Explain *why* you chose an approach when it's non-obvious. ",
-                    "If a request is outside what the tools can do (e.g. publishing, Team Create, marketplace), say so clearly."
-                )
-            }
-        }
-    });
-    let config_content = serde_json::to_string_pretty(&mcp_config)
-        .map_err(|e| format!("Failed to serialize OpenCode config: {e}"))?;
+    let mcp_entry = strip_win_prefix(&mcp_entry);
 
-    log::debug!("Config: {config_content}");
+    Ok(Runtimes {
+        nodejs_bin_dir,
+        mcp_entry,
+    })
+}
 
-    let workspace = crate::paths::workspace_dir()?;
+/// Where the isolated OpenCode instance lives and the environment the
+/// sidecar is spawned with.
+struct SidecarEnv {
+    workspace: std::path::PathBuf,
+    opencode_home: std::path::PathBuf,
+    config_file: std::path::PathBuf,
+    /// OpenCode's plugin cache inside the isolated `XDG_CACHE_HOME`.
+    plugin_cache: std::path::PathBuf,
+    /// Isolated XDG directories plus a minimal `PATH`.
+    vars: Vec<(String, String)>,
+}
 
-    // Create isolated XDG directories under ~/BloxBot/.opencode/
-    // This prevents the bundled OpenCode from reading/writing to the user's
-    // global ~/.config/opencode, ~/.local/share/opencode, etc.
-    let opencode_home = workspace.join(".opencode");
+impl SidecarEnv {
+    fn var(&self, name: &str) -> Option<&str> {
+        self.vars
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Create the isolated XDG directories and build the sidecar environment.
+fn prepare_sidecar_env(runtimes: &Runtimes) -> Result<SidecarEnv, StartupError> {
+    use StartupErrorCode as Code;
+
+    let workspace =
+        crate::paths::workspace_dir().map_err(|e| StartupError::new(Code::Internal, e))?;
+
+    // Create isolated XDG directories in the project's .opencode folder
+    // (see `projects`). This prevents the bundled OpenCode from
+    // reading/writing to the user's global ~/.config/opencode,
+    // ~/.local/share/opencode, etc.
+    let opencode_home = crate::projects::current_opencode_home()
+        .map_err(|e| StartupError::new(Code::Internal, e))?;
     let xdg_data = opencode_home.join("data");
     let xdg_config = opencode_home.join("config");
     let xdg_cache = opencode_home.join("cache");
@@ -452,21 +470,25 @@ async fn do_start(
     // Create directories if they don't exist
     for dir in [&xdg_data, &xdg_config, &xdg_cache, &xdg_state] {
         if !dir.exists() {
-            std::fs::create_dir_all(dir)
-                .map_err(|e| format!("Failed to create directory {}: {e}", dir.display()))?;
+            std::fs::create_dir_all(dir).map_err(|e| {
+                StartupError::new(
+                    Code::ConfigWriteFailed,
+                    format!("Failed to create directory {}: {e}", dir.display()),
+                )
+            })?;
         }
     }
 
-    // Write the config file that OpenCode actually reads on startup.
-    // OPENCODE_CONFIG_CONTENT env var is ignored — OpenCode loads from
-    // {XDG_CONFIG_HOME}/opencode/opencode.json instead.
-    let config_dir = xdg_config.join("opencode");
-    std::fs::create_dir_all(&config_dir)
-        .map_err(|e| format!("Failed to create config dir: {e}"))?;
-    let config_file = config_dir.join("opencode.json");
-    std::fs::write(&config_file, &config_content)
-        .map_err(|e| format!("Failed to write OpenCode config: {e}"))?;
-    log::info!("Wrote OpenCode config to {}", config_file.display());
+    // Provider logins are shared by all projects. Without the link the
+    // project just has its own, so this is not fatal.
+    match crate::paths::workspace_root() {
+        Ok(root) => {
+            if let Err(e) = crate::projects::link_shared_auth(&root, &xdg_data) {
+                log::warn!("Provider logins are not shared with this project: {e}");
+            }
+        }
+        Err(e) => log::warn!("Provider logins are not shared with this project: {e}"),
+    }
 
     // Build a minimal PATH with our bundled Node.js bin directory first,
     // then essential system paths. This ensures npx/npm use our bundled Node.js.
@@ -474,12 +496,13 @@ async fn do_start(
     // On Windows, Tauri resolves resource paths with the \\?\ extended-length prefix
     // (from std::fs::canonicalize). This prefix breaks PATH lookups and child process
     // resolution, so we strip it.
-    let sidecar_dir = crate::paths::sidecar_dir()?;
+    let sidecar_dir =
+        crate::paths::sidecar_dir().map_err(|e| StartupError::new(Code::SpawnFailed, e))?;
 
     #[cfg(unix)]
-    let nodejs_bin = nodejs_bin_dir.to_string_lossy().to_string();
+    let nodejs_bin = runtimes.nodejs_bin_dir.to_string_lossy().to_string();
     #[cfg(windows)]
-    let nodejs_bin = strip_win_prefix(nodejs_bin_dir);
+    let nodejs_bin = strip_win_prefix(&runtimes.nodejs_bin_dir);
 
     #[cfg(unix)]
     let sidecar_path_str = sidecar_dir.to_string_lossy().to_string();
@@ -497,15 +520,283 @@ async fn do_start(
         nodejs_bin, sidecar_path_str
     );
 
+    let path_str = |p: &std::path::Path| p.to_string_lossy().to_string();
+    let vars = vec![
+        ("XDG_DATA_HOME".to_string(), path_str(&xdg_data)),
+        ("XDG_CONFIG_HOME".to_string(), path_str(&xdg_config)),
+        ("XDG_CACHE_HOME".to_string(), path_str(&xdg_cache)),
+        ("XDG_STATE_HOME".to_string(), path_str(&xdg_state)),
+        ("PATH".to_string(), minimal_path),
+    ];
+
+    Ok(SidecarEnv {
+        workspace,
+        config_file: xdg_config.join("opencode").join("opencode.json"),
+        plugin_cache: crate::plugins::cache_dir(&xdg_cache),
+        opencode_home,
+        vars,
+    })
+}
+
+/// What a running sidecar was spawned with. If this differs from what a
+/// fresh start would use, `reconfigure` has to restart the process
+/// instead of reloading it in place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SpawnSignature {
+    ports: crate::config::PortConfig,
+    env: Vec<(String, String)>,
+}
+
+impl SpawnSignature {
+    fn current(env: &SidecarEnv) -> Self {
+        Self {
+            ports: crate::config::get().ports,
+            env: env.vars.clone(),
+        }
+    }
+}
+
+#[cfg(unix)]
+const NODE_CMD: &str = "node";
+#[cfg(windows)]
+const NODE_CMD: &str = "node.exe";
+
+#[cfg(unix)]
+const NPM_CMD: &str = "npm";
+#[cfg(windows)]
+const NPM_CMD: &str = "npm.cmd";
+
+/// Seed the isolated plugin cache and return the plugins that can be
+/// loaded without network access (see `plugins`). With `reuse`, the last
+/// result is returned as long as the plugin list and cache are the same.
+async fn prepare_plugins(
+    state: &SharedOpenCodeState,
+    env: &SidecarEnv,
+    runtimes: &Runtimes,
+    reuse: bool,
+) -> Vec<String> {
+    let cache_dir = env.plugin_cache.clone();
+    let plugins = crate::plugins::configured();
+    if reuse {
+        if let Some(prepared) = state.lock().await.prepared_plugins.as_ref() {
+            if prepared.cache_dir == cache_dir && prepared.plugins == plugins {
+                return prepared.ready.clone();
+            }
+        }
+    }
+
+    let npm = runtimes.nodejs_bin_dir.join(NPM_CMD);
+    let path_env = env.var("PATH").unwrap_or_default().to_string();
+    let ready = {
+        let (cache_dir, plugins) = (cache_dir.clone(), plugins.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            let bundle = crate::plugins::bundle_archive();
+            crate::plugins::prepare(
+                &cache_dir,
+                bundle.as_deref().map_err(String::as_str),
+                &npm,
+                &path_env,
+                &plugins,
+            )
+        })
+        .await
+        .unwrap_or_default()
+    };
+    state.lock().await.prepared_plugins = Some(crate::plugins::Prepared {
+        cache_dir,
+        plugins,
+        ready: ready.clone(),
+    });
+    ready
+}
+
+/// Generate `opencode.json` for the given ports and write it.
+/// `studio_place` is the name of the place open in Studio, if known.
+fn write_opencode_config(
+    env: &SidecarEnv,
+    runtimes: &Runtimes,
+    mcp_port: u16,
+    control_port: u16,
+    plugins: &[String],
+    studio_place: Option<&str>,
+) -> Result<(), String> {
+    let app_config = crate::config::get();
+    let studio_prompt = crate::prompts::render_studio_prompt(studio_place);
+    let instructions = crate::instructions::config_entries(&env.workspace);
+    let config = crate::opencode_config::generate(&crate::opencode_config::ConfigInputs {
+        node_cmd: NODE_CMD,
+        mcp_entry: &runtimes.mcp_entry,
+        mcp_port,
+        control_port,
+        studio_prompt: &studio_prompt,
+        custom_agents: &app_config.custom_agents,
+        tool_policy: &app_config.tool_policy,
+        mcp_servers: &app_config.mcp_servers,
+        plugins,
+        path: env.var("PATH").unwrap_or_default(),
+        local_providers: &app_config.local_providers,
+        instructions: &instructions,
+    });
+    crate::opencode_config::write(&env.config_file, &config)
+}
+
+/// The Studio place name to render the prompt with, recorded as the one
+/// the config was last written with.
+async fn rendered_place(state: &SharedOpenCodeState) -> Option<String> {
+    let mut s = state.lock().await;
+    s.prompt_place = s.studio_place.clone();
+    s.prompt_place.clone()
+}
+
+// ── Core lifecycle ──────────────────────────────────────────────────────
+
+/// Start the OpenCode server. Called automatically on app launch.
+///
+/// Uses `tauri-plugin-shell` sidecar support to spawn the bundled
+/// `opencode` binary. stdout/stderr and process exit are handled via
+/// the shell plugin's event channel, replacing manual BufReader capture
+/// and polling-based exit monitoring.
+pub async fn start_opencode_server(
+    state: SharedOpenCodeState,
+    app: AppHandle,
+) -> Result<u16, String> {
+    // Guard: don't double-start, and never start during app shutdown
+    {
+        let current = state.lock().await;
+        if current.shutting_down {
+            return Err("BloxBot is shutting down".to_string());
+        }
+        if matches!(
+            current.status,
+            OpenCodeStatus::Running | OpenCodeStatus::Starting | OpenCodeStatus::Degraded
+        ) {
+            return Ok(current.port);
+        }
+    }
+
+    set_status(&state, &app, OpenCodeStatus::Starting).await;
+
+    // Run the actual startup logic. If anything fails after this point,
+    // transition status to Error so the frontend can show a retry button
+    // instead of being stuck on "Starting up..." forever. A port taken
+    // after its reservation was released is retried with fresh ports.
+    let mut attempt = 1;
+    let result = loop {
+        let result = do_start(&state, &app, attempt < PORT_ATTEMPTS).await;
+        let port_taken = match &result {
+            Err(e) if e.code == StartupErrorCode::EarlyExit => {
+                crate::ports::is_taken(state.lock().await.port)
+            }
+            Err(e) => e.code == StartupErrorCode::PortTaken,
+            Ok(_) => false,
+        };
+        if !port_taken || attempt == PORT_ATTEMPTS {
+            break match result {
+                Err(e) if port_taken => {
+                    Err(StartupError::new(StartupErrorCode::PortTaken, e.message))
+                }
+                result => result,
+            };
+        }
+        log::warn!("A port was taken before its server could bind it (attempt {attempt} of {PORT_ATTEMPTS}); retrying with new ports");
+        attempt += 1;
+        set_status(&state, &app, OpenCodeStatus::Starting).await;
+    };
+    match result {
+        Ok(port) => Ok(port),
+        Err(e) => {
+            log::error!("Startup failed ({:?}): {}", e.code, e.message);
+            set_startup_error(&state, &app, &e).await;
+            Err(e.message)
+        }
+    }
+}
+
+/// Startup attempts when a released port is taken before the server that
+/// should listen on it binds it.
+const PORT_ATTEMPTS: u32 = 3;
+
+/// Inner startup logic extracted so that any `?` failure is caught by the
+/// caller and translated into an `Error` status transition. Each step is
+/// announced with `set_phase` before it runs. With `retry_ports`, a Studio
+/// MCP server that failed because its ports were taken stops the sidecar
+/// and fails with `PortTaken` instead of leaving Studio disconnected.
+async fn do_start(
+    state: &SharedOpenCodeState,
+    app: &AppHandle,
+    retry_ports: bool,
+) -> Result<u16, StartupError> {
+    use StartupErrorCode as Code;
+
+    // ── Phase: resolve the bundled runtimes ──────────────────────────
+    set_phase(state, app, StartupPhase::ResolvingNode).await;
+    let runtimes = resolve_runtimes()?;
+
+    // ── Phase: clean up stale processes ──────────────────────────────
+    // Kill any stale processes from a previous crash/force-quit before
+    // probing ports. This ensures the allocator gets clean ports.
+    set_phase(state, app, StartupPhase::CleaningStaleProcesses).await;
+    // Rojo is recorded in the registry too; stop it ourselves rather than
+    // have the cleanup kill it. It comes back once the sidecar is healthy.
+    crate::rojo::stop(state, app).await;
+    cleanup_stale_processes();
+    // Brief pause so the OS can release the TCP sockets after killing processes.
+    tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+
+    // ── Phase: allocate ports ────────────────────────────────────────
+    set_phase(state, app, StartupPhase::AllocatingPorts).await;
+    // The reservations keep all three ports bound until just before the
+    // sidecar is spawned, so nothing else can grab them in between.
+    let reserved = crate::ports::allocate(&PortRanges::configured())
+        .map_err(|e| StartupError::new(Code::PortExhausted, e.to_string()))?;
+    let port = reserved.opencode.port;
+    let mcp_port = reserved.bridge.port;
+    let control_port = reserved.control.port;
+    log::info!("OpenCode port: {port}, MCP bridge port: {mcp_port}, control port: {control_port}");
+
+    {
+        let mut s = state.lock().await;
+        s.port = port;
+        s.mcp_port = mcp_port;
+        s.control_port = control_port;
+    }
+
+    // ── Phase: write the OpenCode config ─────────────────────────────
+    set_phase(state, app, StartupPhase::WritingConfig).await;
+    let env = prepare_sidecar_env(&runtimes)?;
+    let plugins = prepare_plugins(state, &env, &runtimes, false).await;
+    let studio_place = rendered_place(state).await;
+    write_opencode_config(
+        &env,
+        &runtimes,
+        mcp_port,
+        control_port,
+        &plugins,
+        studio_place.as_deref(),
+    )
+    .map_err(|e| StartupError::new(Code::ConfigWriteFailed, e))?;
+
+    // ── Phase: spawn the sidecar ─────────────────────────────────────
+    set_phase(state, app, StartupPhase::Spawning).await;
+
+    // Hand the reserved ports over: the sidecar binds the OpenCode port
+    // right away, the launcher binds the other two once OpenCode starts it.
+    // Should another process get in between, the server fails to bind and
+    // `start_opencode_server` retries with new ports.
+    reserved.opencode.release();
+    reserved.bridge.release();
+    reserved.control.release();
+
     // Spawn the sidecar via the shell plugin. This automatically resolves
     // the binary from the `externalBin` config in tauri.conf.json.
     let (rx, child) = app
         .shell()
         .sidecar("opencode")
         .map_err(|e| {
-            let msg = format!("Failed to create sidecar command: {e}");
-            log::error!("{msg}");
-            msg
+            StartupError::new(
+                Code::SpawnFailed,
+                format!("Failed to create sidecar command: {e}"),
+            )
         })?
         .args([
             "serve",
@@ -517,96 +808,189 @@ async fn do_start(
             "--log-level",
             "DEBUG",
         ])
-        .current_dir(&workspace)
-        // Isolated XDG directories
-        .env("XDG_DATA_HOME", &xdg_data)
-        .env("XDG_CONFIG_HOME", &xdg_config)
-        .env("XDG_CACHE_HOME", &xdg_cache)
-        .env("XDG_STATE_HOME", &xdg_state)
-        // Minimal PATH with bundled node/npm/npx first
-        .env("PATH", &minimal_path)
+        .current_dir(&env.workspace)
+        // Isolated XDG directories and a minimal PATH with bundled
+        // node/npm/npx first
+        .envs(env.vars.clone())
         .spawn()
         .map_err(|e| {
-            let msg = format!("Failed to start OpenCode server: {e}");
-            log::error!("{msg}");
-            msg
+            StartupError::new(
+                Code::SpawnFailed,
+                format!("Failed to start OpenCode server: {e}"),
+            )
         })?;
 
-    log::info!("Isolated environment: {}", opencode_home.display());
-    log::debug!("PATH: {}", minimal_path);
+    log::info!("Isolated environment: {}", env.opencode_home.display());
+    log::debug!("Sidecar environment: {:?}", env.vars);
 
-    {
+    crate::process_registry::record(ProcessRole::Sidecar, child.pid());
+
+    let spawned_at = Instant::now();
+    let generation = {
         let mut s = state.lock().await;
         s.child = Some(child);
-    }
+        s.child_generation += 1;
+        s.last_output_at = None;
+        s.launcher_pid = None;
+        s.mcp_server_pid = None;
+        s.spawn_signature = Some(SpawnSignature::current(&env));
+        s.child_generation
+    };
 
     // Spawn an event handler for stdout, stderr, and process exit.
     // This replaces both the BufReader capture tasks and the polling-based
     // spawn_exit_monitor from the old tokio::process implementation.
-    spawn_event_handler(rx, Arc::clone(state), app.clone());
+    spawn_event_handler(rx, Arc::clone(state), app.clone(), generation);
+
+    // ── Phase: wait for the health endpoint ──────────────────────────
+    set_phase(state, app, StartupPhase::WaitingForHealth).await;
 
     // Wait for the server to be ready by polling the health endpoint.
     // If the process exits (detected via the event handler setting the
     // status to Error), bail out immediately instead of waiting the full
     // timeout — this avoids a ~35 second hang when the binary crashes on
     // launch.
+    //
+    // The deadline adapts: a first launch may spend minutes installing
+    // plugins, so past the base timeout we keep waiting for as long as
+    // the sidecar's output shows it is still doing something.
+    let timing = crate::config::get().startup;
     let health_url = format!("http://{LOOPBACK}:{port}/global/health");
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(2))
         .build()
         .unwrap_or_default();
 
-    let mut healthy = false;
-    for _ in 0..15 {
+    let mut extended = false;
+    loop {
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
         // Check if the process already exited (the event handler sets
         // child to None and status to Error on termination).
-        {
-            let s = state.lock().await;
-            if s.child.is_none() {
-                // Process is gone — return whatever error the event
-                // handler already set, or a generic message.
-                if let OpenCodeStatus::Error(ref msg) = s.status {
-                    let err = msg.clone();
-                    log::error!("Process exited before becoming healthy: {err}");
-                    return Err(err);
-                }
-                let err = "OpenCode process exited before becoming healthy".to_string();
-                log::error!("{err}");
-                drop(s);
-                set_status(state, app, OpenCodeStatus::Error(err.clone())).await;
-                return Err(err);
-            }
+        if let Some(err) = early_exit_error(state).await {
+            return Err(err);
         }
 
         if let Ok(resp) = client.get(&health_url).send().await {
             if resp.status().is_success() {
-                healthy = true;
                 break;
             }
         }
-    }
 
-    if healthy {
-        log::info!("Server healthy on port {port}");
-        set_status(state, app, OpenCodeStatus::Running).await;
-        Ok(port)
-    } else {
+        let elapsed = spawned_at.elapsed();
+        if elapsed < timing.health_timeout() {
+            continue;
+        }
+        // A sidecar that has printed nothing yet shows no progress, so it
+        // only gets the base deadline.
+        let last_output = state.lock().await.last_output_at;
+        let silent_for = last_output.unwrap_or(spawned_at).elapsed();
+        if last_output.is_some()
+            && silent_for < timing.stall_timeout()
+            && elapsed < timing.max_health_timeout()
+        {
+            if !extended {
+                log::info!(
+                    "Server not healthy after {}ms but still producing output; extending the deadline",
+                    elapsed.as_millis()
+                );
+                extended = true;
+            }
+            continue;
+        }
+
         // One final check: the process may have died on the last iteration.
-        let s = state.lock().await;
-        if let OpenCodeStatus::Error(ref msg) = s.status {
-            let err = msg.clone();
-            log::error!("Process exited during health check: {err}");
+        if let Some(err) = early_exit_error(state).await {
             return Err(err);
         }
-        drop(s);
+        let why = if last_output.is_none() || elapsed >= timing.max_health_timeout() {
+            format!("no health response within {}s", elapsed.as_secs())
+        } else {
+            format!("no output for {}s", silent_for.as_secs())
+        };
+        return Err(StartupError::new(
+            Code::HealthTimeout,
+            format!("OpenCode server started but health check timed out ({why})"),
+        ));
+    }
+    log::info!(
+        "Server healthy on port {port} after {}ms",
+        spawned_at.elapsed().as_millis()
+    );
 
-        let err = "OpenCode server started but health check timed out".to_string();
-        log::error!("{err}");
-        set_status(state, app, OpenCodeStatus::Error(err.clone())).await;
-        Err(err)
+    // ── Phase: wait for the roblox-studio MCP server ─────────────────
+    // Not fatal: a failed MCP server is surfaced by `poll_studio_status`
+    // with its own error, and the chat is still usable without Studio.
+    set_phase(state, app, StartupPhase::McpConnecting).await;
+    let mcp_status = wait_for_mcp(port).await;
+    if retry_ports
+        && mcp_status.as_deref() == Some("failed")
+        && (crate::ports::is_taken(mcp_port) || crate::ports::is_taken(control_port))
+    {
+        stop_all(state, app).await;
+        return Err(StartupError::new(
+            Code::PortTaken,
+            format!("The Studio MCP server failed and port {mcp_port} or {control_port} is in use by another process"),
+        ));
+    }
+    record_mcp_processes(state, control_port).await;
+    if let Some(err) = early_exit_error(state).await {
+        return Err(err);
+    }
+
+    set_status(state, app, OpenCodeStatus::Running).await;
+    spawn_watchdog(Arc::clone(state), app.clone(), generation);
+    // Not awaited: Rojo failures are reported through its own status.
+    tauri::async_runtime::spawn(crate::rojo::start(Arc::clone(state), app.clone()));
+    Ok(port)
+}
+
+/// If the sidecar has already exited, build the matching `EarlyExit`
+/// error — reusing the message the event handler set, if any.
+async fn early_exit_error(state: &SharedOpenCodeState) -> Option<StartupError> {
+    let s = state.lock().await;
+    if s.child.is_some() {
+        return None;
     }
+    let msg = match s.status {
+        OpenCodeStatus::Error(ref msg) => msg.clone(),
+        _ => "OpenCode process exited before becoming healthy".to_string(),
+    };
+    Some(StartupError::new(StartupErrorCode::EarlyExit, msg))
+}
+
+/// Poll `/mcp` until the `roblox-studio` entry leaves its pending state,
+/// giving up quietly after a few seconds. Returns the status it reached.
+async fn wait_for_mcp(port: u16) -> Option<String> {
+    let Ok(workspace) = crate::paths::workspace_dir() else {
+        return None;
+    };
+    let workspace_str = workspace.to_string_lossy().to_string();
+    let mcp_url = format!("http://{LOOPBACK}:{port}/mcp");
+
+    for _ in 0..10 {
+        if let Ok(resp) = http_client()
+            .get(&mcp_url)
+            .header("x-opencode-directory", &workspace_str)
+            .query(&[("directory", &workspace_str)])
+            .send()
+            .await
+        {
+            if let Ok(body) = resp.json::<serde_json::Value>().await {
+                let status = body
+                    .get("roblox-studio")
+                    .and_then(|rs| rs.get("status"))
+                    .and_then(|v| v.as_str());
+                if let Some(status) = status {
+                    log::info!("roblox-studio MCP status: {status}");
+                    return Some(status.to_string());
+                }
+            }
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    }
+    log::warn!("roblox-studio MCP server did not report a status during startup");
+    None
 }
 
 /// Spawn an event handler task that processes stdout/stderr and handles
@@ -618,6 +1002,7 @@ fn spawn_event_handler(
     rx: tauri::async_runtime::Receiver<CommandEvent>,
     state: SharedOpenCodeState,
     app: AppHandle,
+    generation: u64,
 ) {
     std::thread::spawn(move || {
         let rt = match tokio::runtime::Builder::new_current_thread()
@@ -632,7 +1017,7 @@ fn spawn_event_handler(
         };
 
         rt.block_on(async move {
-            process_events(rx, &state, &app).await;
+            process_events(rx, &state, &app, generation).await;
         });
     });
 }
@@ -680,6 +1065,7 @@ async fn process_events(
     mut rx: tauri::async_runtime::Receiver<CommandEvent>,
     state: &SharedOpenCodeState,
     app: &AppHandle,
+    generation: u64,
 ) {
     while let Some(event) = rx.recv().await {
         match event {
@@ -690,6 +1076,7 @@ async fn process_events(
                     log::trace!(target: "opencode::stdout", "{trimmed}");
                 } else {
                     log::info!(target: "opencode::stdout", "{trimmed}");
+                    note_output(state, generation).await;
                 }
             }
             CommandEvent::Stderr(line) => {
@@ -701,6 +1088,7 @@ async fn process_events(
                 if is_noisy_sidecar_line(trimmed) {
                     log::trace!(target: "opencode::stderr", "{trimmed}");
                 } else {
+                    note_output(state, generation).await;
                     match parse_sidecar_level(trimmed) {
                         log::Level::Error => log::error!(target: "opencode::stderr", "{trimmed}"),
                         log::Level::Warn => log::warn!(target: "opencode::stderr", "{trimmed}"),
@@ -711,7 +1099,7 @@ async fn process_events(
                 }
             }
             CommandEvent::Terminated(payload) => {
-                handle_process_exit(state, app, &payload).await;
+                handle_process_exit(state, app, &payload, generation).await;
                 return;
             }
             _ => {}
@@ -719,20 +1107,41 @@ async fn process_events(
     }
 }
 
-/// Handle process termination. Sets the appropriate status so the
-/// frontend can show an error with a manual retry button.
+/// Record that the sidecar printed something meaningful. Noise lines are
+/// excluded on purpose: our own health polls show up there and must not
+/// count as progress.
+async fn note_output(state: &SharedOpenCodeState, generation: u64) {
+    let mut s = state.lock().await;
+    if s.child_generation == generation {
+        s.last_output_at = Some(Instant::now());
+    }
+}
+
+/// Handle process termination. A crash of a healthy server is handed to
+/// the restart supervisor; a crash during startup is reported as an error
+/// (the startup path or an in-flight supervisor decides what happens next).
 async fn handle_process_exit(
     state: &SharedOpenCodeState,
     app: &AppHandle,
     payload: &tauri_plugin_shell::process::TerminatedPayload,
+    generation: u64,
 ) {
     let mut s = state.lock().await;
+    if s.child_generation != generation {
+        // The process was stopped on purpose (stop_all) or has already
+        // been replaced by a newer one.
+        log::debug!("Ignoring exit of superseded sidecar (generation {generation})");
+        return;
+    }
     s.child = None;
+    let was_running = matches!(s.status, OpenCodeStatus::Running | OpenCodeStatus::Degraded);
 
     if payload.code == Some(0) {
         log::info!("Process exited cleanly");
         s.status = OpenCodeStatus::Stopped;
-        emit_status(app, &s.status, s.port);
+        s.error_code = None;
+        s.running_since = None;
+        emit_status(app, &s);
         return;
     }
 
@@ -751,42 +1160,560 @@ async fn handle_process_exit(
             None => "The server stopped unexpectedly.".to_string(),
         },
     };
+    s.last_exit_reason = Some(raw_msg);
+
+    if was_running {
+        end_run(&mut s);
+        drop(s);
+        spawn_supervisor(Arc::clone(state), app.clone(), user_msg);
+        return;
+    }
+
+    s.running_since = None;
     s.status = OpenCodeStatus::Error(user_msg);
-    emit_status(app, &s.status, s.port);
+    s.error_code = Some(StartupErrorCode::EarlyExit);
+    emit_status(app, &s);
 }
 
-/// Gracefully stop everything: MCP server (via launcher control endpoint),
-/// then the OpenCode sidecar. Waits briefly between the MCP shutdown
-/// request and killing the sidecar so the launcher has time to terminate
-/// its child process tree.
-pub async fn stop_all(state: &SharedOpenCodeState, app: &AppHandle) {
-    let (mcp_port, has_child) = {
-        let s = state.lock().await;
-        (s.mcp_port, s.child.is_some())
+/// Ask the MCP launcher's control endpoint for its own PID and the MCP
+/// server's PID and record both in the process registry and in `state`. These processes
+/// are spawned by OpenCode, not by us, so this is the only way to learn
+/// their PIDs. The registry is only rewritten when either PID changed.
+async fn record_mcp_processes(state: &SharedOpenCodeState, control_port: u16) {
+    let url = format!("http://{LOOPBACK}:{control_port}/health");
+    let body = match http_client().get(&url).send().await {
+        Ok(resp) if resp.status().is_success() => resp
+            .json::<serde_json::Value>()
+            .await
+            .unwrap_or(serde_json::Value::Null),
+        _ => return,
     };
+    let pid_of = |key: &str| body.get(key).and_then(|v| v.as_u64()).map(|p| p as u32);
+    let launcher_pid = pid_of("launcherPid");
+    let mcp_server_pid = pid_of("pid");
+    {
+        let s = state.lock().await;
+        if s.launcher_pid == launcher_pid && s.mcp_server_pid == mcp_server_pid {
+            return;
+        }
+    }
+    if let Some(pid) = launcher_pid {
+        crate::process_registry::record(ProcessRole::Launcher, pid);
+    }
+    if let Some(pid) = mcp_server_pid {
+        crate::process_registry::record(ProcessRole::McpServer, pid);
+    }
+
+    let mut s = state.lock().await;
+    s.launcher_pid = launcher_pid;
+    s.mcp_server_pid = mcp_server_pid;
+}
 
-    if !has_child && mcp_port == 0 {
+// ── Liveness watchdog ───────────────────────────────────────────────────
+
+/// Keep probing a healthy server for as long as its child generation is
+/// current. `do_start` only waits for the *first* successful health check;
+/// after that a hung process would otherwise stay `Running` forever.
+///
+/// A failed probe moves the status to `Degraded`; a successful one moves it
+/// back to `Running`. After `failure_threshold` consecutive failures the
+/// child is killed and handed to the restart supervisor.
+fn spawn_watchdog(state: SharedOpenCodeState, app: AppHandle, generation: u64) {
+    let cfg = crate::config::get().watchdog;
+    if !cfg.enabled || cfg.failure_threshold == 0 {
+        log::info!("Liveness watchdog disabled");
         return;
     }
 
-    // Step 1: Ask the launcher to gracefully shut down the MCP server.
-    if mcp_port > 0 {
-        shutdown_mcp_server(mcp_port).await;
-        // Give the launcher time to SIGTERM the child and exit (it waits
-        // up to 2s internally before SIGKILL). 1.5s is enough in the
-        // happy path; the sidecar kill below is the final backstop.
-        tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+    tauri::async_runtime::spawn(async move {
+        let interval = std::time::Duration::from_millis(cfg.interval_ms.max(500));
+        let mut failures = 0u32;
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let port = {
+                let s = state.lock().await;
+                if s.child_generation != generation
+                    || !matches!(s.status, OpenCodeStatus::Running | OpenCodeStatus::Degraded)
+                {
+                    log::debug!("Watchdog for generation {generation} exiting");
+                    return;
+                }
+                s.port
+            };
+
+            match probe_liveness(port).await {
+                Ok(()) => {
+                    if failures > 0 {
+                        log::info!("OpenCode responsive again after {failures} failed probe(s)");
+                    }
+                    failures = 0;
+                    let mut s = state.lock().await;
+                    if s.child_generation == generation
+                        && matches!(s.status, OpenCodeStatus::Degraded)
+                    {
+                        s.status = OpenCodeStatus::Running;
+                        emit_status(&app, &s);
+                    }
+                }
+                Err(e) => {
+                    failures += 1;
+                    log::warn!(
+                        "Liveness probe failed ({failures}/{}): {e}",
+                        cfg.failure_threshold
+                    );
+                    let mut s = state.lock().await;
+                    if s.child_generation != generation {
+                        return;
+                    }
+                    if failures < cfg.failure_threshold {
+                        if matches!(s.status, OpenCodeStatus::Running) {
+                            s.status = OpenCodeStatus::Degraded;
+                            emit_status(&app, &s);
+                        }
+                        continue;
+                    }
+
+                    // Controlled restart: take the child and bump the
+                    // generation so its exit isn't reported as a crash.
+                    log::error!("OpenCode unresponsive, restarting: {e}");
+                    if let Some(child) = s.child.take() {
+                        let _ = child.kill();
+                    }
+                    s.child_generation += 1;
+                    end_run(&mut s);
+                    s.last_exit_reason = Some(format!(
+                        "Unresponsive after {failures} failed liveness probes: {e}"
+                    ));
+                    drop(s);
+                    spawn_supervisor(
+                        Arc::clone(&state),
+                        app.clone(),
+                        "The server stopped responding.".to_string(),
+                    );
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// One liveness probe: the global health endpoint must answer, and the
+/// per-directory `/mcp` endpoint must answer too (it exercises the
+/// instance that actually serves chat requests).
+async fn probe_liveness(port: u16) -> Result<(), String> {
+    let client = http_client();
+
+    let health_url = format!("http://{LOOPBACK}:{port}/global/health");
+    match client.get(&health_url).send().await {
+        Ok(resp) if resp.status().is_success() => {}
+        Ok(resp) => return Err(format!("/global/health returned HTTP {}", resp.status())),
+        Err(e) => return Err(format!("/global/health: {e}")),
     }
 
-    // Step 2: Kill the OpenCode sidecar process.
-    let mut s = state.lock().await;
-    if let Some(child) = s.child.take() {
-        let _ = child.kill();
+    let workspace_str = crate::paths::workspace_dir()?.to_string_lossy().to_string();
+    let mcp_url = format!("http://{LOOPBACK}:{port}/mcp");
+    match client
+        .get(&mcp_url)
+        .header("x-opencode-directory", &workspace_str)
+        .query(&[("directory", &workspace_str)])
+        .send()
+        .await
+    {
+        Ok(resp) if resp.status().is_success() => Ok(()),
+        Ok(resp) => Err(format!("/mcp returned HTTP {}", resp.status())),
+        Err(e) => Err(format!("/mcp: {e}")),
+    }
+}
+
+// ── Restart supervisor ──────────────────────────────────────────────────
+
+/// Close the current run before handing it to the supervisor. Decides
+/// whether the failure counts against the current streak: a server that
+/// stayed healthy for the reset window starts over.
+fn end_run(s: &mut OpenCodeState) {
+    let policy = crate::config::get().restart_policy;
+    let stable = s
+        .running_since
+        .is_some_and(|since| since.elapsed() >= policy.reset_window());
+    if stable {
+        s.restart_count = 0;
+    }
+    s.running_since = None;
+}
+
+/// Restart the sidecar after an unexpected exit, following the
+/// `RestartPolicy` from `AppConfig`. Each failed attempt doubles the delay
+/// until `max_attempts` is reached, at which point the status settles on
+/// `Error` and the frontend falls back to its manual retry button.
+///
+/// The supervisor backs off silently if anything else changes the status
+/// while it sleeps (user-initiated restart, app shutdown).
+fn spawn_supervisor(state: SharedOpenCodeState, app: AppHandle, user_msg: String) {
+    tauri::async_runtime::spawn(async move {
+        let policy = crate::config::get().restart_policy;
+        let mut user_msg = user_msg;
+
+        loop {
+            let delay = {
+                let mut s = state.lock().await;
+                if s.restart_count >= policy.max_attempts {
+                    log::error!(
+                        "OpenCode crashed {} time(s) in a row, giving up on automatic restarts",
+                        s.restart_count
+                    );
+                    s.status = OpenCodeStatus::Error(user_msg);
+                    s.error_code = Some(StartupErrorCode::TooManyCrashes);
+                    emit_status(&app, &s);
+                    return;
+                }
+                s.restart_count += 1;
+                s.status = OpenCodeStatus::Restarting;
+                s.error_code = None;
+                emit_status(&app, &s);
+                let delay = policy.backoff(s.restart_count);
+                log::warn!(
+                    "Restarting OpenCode in {}ms (attempt {}/{})",
+                    delay.as_millis(),
+                    s.restart_count,
+                    policy.max_attempts
+                );
+                delay
+            };
+
+            tokio::time::sleep(delay).await;
+
+            let control_port = {
+                let s = state.lock().await;
+                if !matches!(s.status, OpenCodeStatus::Restarting) {
+                    log::info!("Automatic restart cancelled (status is now {:?})", s.status);
+                    return;
+                }
+                s.control_port
+            };
+
+            // The MCP launcher may have outlived the sidecar; ask it to
+            // exit so the new server can take over the bridge port.
+            if control_port > 0 {
+                shutdown_mcp_server(control_port).await;
+            }
+
+            match start_opencode_server(Arc::clone(&state), app.clone()).await {
+                Ok(port) => {
+                    log::info!("OpenCode restarted on port {port}");
+                    return;
+                }
+                Err(e) => {
+                    log::error!("Automatic restart failed: {e}");
+                    let s = state.lock().await;
+                    if !matches!(s.status, OpenCodeStatus::Error(_)) {
+                        return;
+                    }
+                    user_msg = e;
+                }
+            }
+        }
+    });
+}
+
+/// How long `stop_all` waits for processes to exit after asking them to,
+/// before escalating to a forced kill.
+const STOP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// What `stop_all` had to do to bring the process tree down.
+#[derive(Debug, Default)]
+pub struct StopReport {
+    /// Processes that exited on their own after being asked to.
+    pub exited: Vec<(ProcessRole, u32)>,
+    /// Processes still alive after `STOP_TIMEOUT` that were force-killed.
+    pub killed: Vec<(ProcessRole, u32)>,
+}
+
+/// Gracefully stop everything: MCP server (via launcher control endpoint),
+/// then the OpenCode sidecar and Rojo (via `SIGTERM` to their process
+/// trees). Waits until every owned process has actually exited, and
+/// force-kills only what is still alive after `STOP_TIMEOUT`.
+pub async fn stop_all<R: Runtime>(state: &SharedOpenCodeState, app: &AppHandle<R>) -> StopReport {
+    // Detach the child first. Bumping the generation tells its event
+    // handler and watchdog that the exit is intentional.
+    let (control_port, mut child, mut rojo_child) = {
+        let mut s = state.lock().await;
+        let child = s.child.take();
+        s.child_generation += 1;
+        s.running_since = None;
+        (s.control_port, child, s.rojo.detach())
+    };
+
+    let mut pending: Vec<(ProcessRole, u32)> = crate::process_registry::verify_owned()
+        .owned
+        .iter()
+        .map(|p| (p.role, p.pid))
+        .collect();
+    if let Some(pid) = child.as_ref().map(CommandChild::pid) {
+        if !pending.iter().any(|&(_, p)| p == pid) {
+            pending.push((ProcessRole::Sidecar, pid));
+        }
     }
+    if let Some(pid) = rojo_child.as_ref().map(CommandChild::pid) {
+        if !pending.iter().any(|&(_, p)| p == pid) {
+            pending.push((ProcessRole::Rojo, pid));
+        }
+    }
+
+    let mut report = StopReport::default();
+
+    if !pending.is_empty() || control_port > 0 {
+        // Step 1: Ask the launcher to gracefully shut down the MCP server.
+        if control_port > 0 {
+            shutdown_mcp_server(control_port).await;
+        }
+
+        // Step 2: Ask the sidecar (and anything it spawned) and Rojo to
+        // exit.
+        for c in child.iter().chain(rojo_child.iter()) {
+            crate::process_registry::request_terminate(c.pid());
+        }
+
+        // Step 3: Wait for everything to actually be gone.
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        loop {
+            pending.retain(|&(role, pid)| {
+                if crate::process_registry::is_alive(pid) {
+                    return true;
+                }
+                crate::process_registry::forget(pid);
+                report.exited.push((role, pid));
+                false
+            });
+            if pending.is_empty() || Instant::now() >= deadline {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+
+        // Step 4: Escalate for whatever ignored the polite request.
+        for (role, pid) in pending {
+            log::warn!("{role:?} PID {pid} did not exit within {STOP_TIMEOUT:?}; killing it");
+            match child
+                .take_if(|c| c.pid() == pid)
+                .or_else(|| rojo_child.take_if(|c| c.pid() == pid))
+            {
+                Some(c) => {
+                    let _ = c.kill();
+                }
+                None => crate::process_registry::terminate(pid),
+            }
+            crate::process_registry::forget(pid);
+            report.killed.push((role, pid));
+        }
+    }
+
+    let mut s = state.lock().await;
     s.status = OpenCodeStatus::Stopped;
+    s.error_code = None;
     s.port = 0;
     s.mcp_port = 0;
-    emit_status(app, &s.status, 0);
+    s.control_port = 0;
+    s.launcher_pid = None;
+    s.mcp_server_pid = None;
+    emit_status(app, &s);
+    if s.rojo.status != crate::rojo::RojoStatus::Inactive {
+        s.rojo.status = crate::rojo::RojoStatus::Stopped;
+        crate::rojo::emit_status(app, &s.rojo);
+    }
+    report
+}
+
+// ── Reconfigure ─────────────────────────────────────────────────────────
+
+/// What `reconfigure` ended up doing.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReconfigureOutcome {
+    /// `opencode.json` was rewritten and the running server reloaded it.
+    Reloaded,
+    /// Ports or environment changed, so the sidecar was restarted.
+    Restarted,
+    /// Nothing is running; the new config applies on the next start.
+    NotRunning,
+}
+
+/// Apply config changes to the running server.
+///
+/// Regenerates `opencode.json` and asks OpenCode to dispose its instance,
+/// which makes it re-read the config (agents, MCP servers, plugins) on
+/// the next request. The process — and with it the MCP bridge port the
+/// Studio plugin is connected to — keeps running. Only when the ports or
+/// the sidecar environment changed is a full restart performed.
+pub async fn reconfigure(
+    state: &SharedOpenCodeState,
+    app: &AppHandle,
+) -> Result<ReconfigureOutcome, String> {
+    let (port, mcp_port, control_port, spawned_with) = {
+        let s = state.lock().await;
+        match s.status {
+            OpenCodeStatus::Running | OpenCodeStatus::Degraded => {}
+            OpenCodeStatus::Starting | OpenCodeStatus::Restarting => {
+                return Err("OpenCode is starting; try again once it is running".to_string());
+            }
+            OpenCodeStatus::Stopped | OpenCodeStatus::Error(_) => {
+                return Ok(ReconfigureOutcome::NotRunning);
+            }
+        }
+        (
+            s.port,
+            s.mcp_port,
+            s.control_port,
+            s.spawn_signature.clone(),
+        )
+    };
+
+    let runtimes = resolve_runtimes().map_err(|e| e.message)?;
+    let env = prepare_sidecar_env(&runtimes).map_err(|e| e.message)?;
+
+    if spawned_with.as_ref() != Some(&SpawnSignature::current(&env)) {
+        log::info!("Ports or sidecar environment changed; restarting OpenCode");
+        restart(state, app).await?;
+        return Ok(ReconfigureOutcome::Restarted);
+    }
+
+    // Startup checked the cache; only a changed plugin list needs it again.
+    let plugins = prepare_plugins(state, &env, &runtimes, true).await;
+    let studio_place = rendered_place(state).await;
+    write_opencode_config(
+        &env,
+        &runtimes,
+        mcp_port,
+        control_port,
+        &plugins,
+        studio_place.as_deref(),
+    )?;
+
+    if let Err(e) = dispose_instance(port, &env.workspace).await {
+        log::warn!("Instance reload failed ({e}); falling back to a full restart");
+        restart(state, app).await?;
+        return Ok(ReconfigureOutcome::Restarted);
+    }
+    log::info!("OpenCode reloaded its configuration");
+
+    // Disposing the instance also closes the MCP clients, so the launcher
+    // comes back with new PIDs.
+    wait_for_mcp(port).await;
+    record_mcp_processes(state, control_port).await;
+    Ok(ReconfigureOutcome::Reloaded)
+}
+
+/// How often `reconfigure_when_idle` checks whether the sessions are done.
+const IDLE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Failed `/session/status` checks in a row after which
+/// `reconfigure_when_idle` stops waiting and reloads anyway.
+const MAX_STATUS_FAILURES: u32 = 15;
+
+/// Like `reconfigure`, but for changes nobody explicitly asked to apply
+/// right now (edited instruction files, a newly connected place).
+/// Disposing the instance aborts any turn in flight, so this waits in the
+/// background until no session of the workspace is busy. Requests made
+/// while one is already waiting are folded into it; if OpenCode stops in
+/// the meantime, the next start picks the change up anyway.
+pub fn reconfigure_when_idle(state: &SharedOpenCodeState, app: &AppHandle, reason: &'static str) {
+    let state = Arc::clone(state);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        {
+            let mut s = state.lock().await;
+            if s.reload_pending {
+                return;
+            }
+            s.reload_pending = true;
+        }
+        let mut failures = 0;
+        loop {
+            tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+            let port = {
+                let s = state.lock().await;
+                match s.status {
+                    OpenCodeStatus::Running | OpenCodeStatus::Degraded => s.port,
+                    OpenCodeStatus::Starting | OpenCodeStatus::Restarting => continue,
+                    OpenCodeStatus::Stopped | OpenCodeStatus::Error(_) => break,
+                }
+            };
+            match sessions_busy(port).await {
+                Ok(false) => {}
+                Ok(true) => {
+                    failures = 0;
+                    continue;
+                }
+                Err(e) if failures + 1 < MAX_STATUS_FAILURES => {
+                    failures += 1;
+                    log::debug!("Cannot tell whether sessions are busy: {e}");
+                    continue;
+                }
+                // The check may never work again; waiting forever would
+                // leave the change unapplied for the rest of the session.
+                Err(e) => log::warn!(
+                    "Cannot tell whether sessions are busy after {MAX_STATUS_FAILURES} tries ({e}); applying {reason} anyway"
+                ),
+            }
+            // Cleared first so a change made during the reload gets its
+            // own.
+            state.lock().await.reload_pending = false;
+            match reconfigure(&state, &app).await {
+                Ok(outcome) => log::info!("{reason} applied ({outcome:?})"),
+                Err(e) => log::warn!("{reason} not applied: {e}"),
+            }
+            return;
+        }
+        state.lock().await.reload_pending = false;
+    });
+}
+
+/// `true` if any session of the workspace is mid-turn, from
+/// `GET /session/status` (sessions missing from the map are idle).
+async fn sessions_busy(port: u16) -> Result<bool, String> {
+    let workspace = crate::paths::workspace_dir()?;
+    let workspace_str = workspace.to_string_lossy().to_string();
+    let resp = http_client()
+        .get(format!("http://{LOOPBACK}:{port}/session/status"))
+        .header("x-opencode-directory", &workspace_str)
+        .query(&[("directory", &workspace_str)])
+        .send()
+        .await
+        .map_err(|e| format!("/session/status: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!("/session/status returned HTTP {}", resp.status()));
+    }
+    let body = resp
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("/session/status returned invalid JSON: {e}"))?;
+    Ok(body.as_object().is_some_and(|sessions| {
+        sessions
+            .values()
+            .any(|status| status.get("type").and_then(|t| t.as_str()) != Some("idle"))
+    }))
+}
+
+/// `POST /instance/dispose` for our workspace directory.
+async fn dispose_instance(port: u16, workspace: &std::path::Path) -> Result<(), String> {
+    let workspace_str = workspace.to_string_lossy().to_string();
+    let url = format!("http://{LOOPBACK}:{port}/instance/dispose");
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+    match client
+        .post(&url)
+        .header("x-opencode-directory", &workspace_str)
+        .query(&[("directory", &workspace_str)])
+        .send()
+        .await
+    {
+        Ok(resp) if resp.status().is_success() => Ok(()),
+        Ok(resp) => Err(format!("/instance/dispose returned HTTP {}", resp.status())),
+        Err(e) => Err(format!("/instance/dispose: {e}")),
+    }
 }
 
 // ── Tauri commands ──────────────────────────────────────────────────────
@@ -801,21 +1728,63 @@ pub async fn get_opencode_status(
     Ok((s.status.clone(), s.port))
 }
 
-/// Restart the OpenCode server. Gracefully tears down all processes
-/// (MCP + sidecar) then starts fresh. Called from the frontend retry button.
-#[tauri::command]
-pub async fn restart_opencode(
-    state: tauri::State<'_, SharedOpenCodeState>,
-    app: AppHandle,
-) -> Result<u16, String> {
+/// Gracefully tear down all processes (MCP + sidecar) then start fresh.
+pub async fn restart(state: &SharedOpenCodeState, app: &AppHandle) -> Result<u16, String> {
     // Stop everything first (no-op if already stopped)
-    stop_all(state.inner(), &app).await;
+    stop_all(state, app).await;
+    // A manual restart starts a fresh supervisor streak.
+    {
+        let mut s = state.lock().await;
+        s.restart_count = 0;
+        s.last_exit_reason = None;
+        s.rojo.restart_count = 0;
+    }
     // Clean up any orphans that survived
     cleanup_stale_processes();
     // Small delay for ports to be released by the OS
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
     // Start fresh
-    start_opencode_server(state.inner().clone(), app).await
+    start_opencode_server(Arc::clone(state), app.clone()).await
+}
+
+/// Restart the OpenCode server. Called from the frontend retry button.
+#[tauri::command]
+pub async fn restart_opencode(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+) -> Result<u16, String> {
+    restart(state.inner(), &app).await
+}
+
+/// Regenerate the OpenCode config and apply it, restarting the sidecar
+/// only if its ports or environment changed.
+#[tauri::command]
+pub async fn reconfigure_opencode(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+) -> Result<ReconfigureOutcome, String> {
+    reconfigure(state.inner(), &app).await
+}
+
+/// Fetch OpenCode's per-server MCP status map for the workspace instance.
+async fn fetch_mcp_status(port: u16, workspace: &str) -> Result<serde_json::Value, String> {
+    let mcp_url = format!("http://{LOOPBACK}:{port}/mcp");
+    let resp = http_client()
+        .get(&mcp_url)
+        .header("x-opencode-directory", workspace)
+        .query(&[("directory", workspace)])
+        .send()
+        .await
+        .map_err(|e| format!("OpenCode /mcp request failed: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!("OpenCode /mcp returned HTTP {}", resp.status()));
+    }
+    let body = resp
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("OpenCode /mcp returned invalid JSON: {e}"))?;
+    log::trace!("OpenCode /mcp response: {body}");
+    Ok(body)
 }
 
 /// Combined studio status poll.  Queries both the OpenCode server (for
@@ -824,8 +1793,9 @@ pub async fn restart_opencode(
 #[tauri::command]
 pub async fn poll_studio_status(
     state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
 ) -> Result<StudioStatusResult, String> {
-    let (oc_port, mcp_port) = {
+    let (oc_port, mcp_port, control_port) = {
         let s = state.lock().await;
         // If OpenCode isn't running yet there's nothing to poll.
         if !matches!(s.status, OpenCodeStatus::Running) {
@@ -834,70 +1804,55 @@ pub async fn poll_studio_status(
                 error: None,
             });
         }
-        (s.port, s.mcp_port)
+        (s.port, s.mcp_port, s.control_port)
     };
     let workspace = crate::paths::workspace_dir()?;
     let client = http_client();
 
     // ── Step 1: ask OpenCode for the MCP server status ─────────────
     let workspace_str = workspace.to_string_lossy().to_string();
-    let mcp_url = format!("http://{LOOPBACK}:{oc_port}/mcp");
     let mut mcp_connected = false;
 
-    match client
-        .get(&mcp_url)
-        .header("x-opencode-directory", &workspace_str)
-        .query(&[("directory", &workspace_str)])
-        .send()
-        .await
-    {
-        Ok(resp) if resp.status().is_success() => {
-            if let Ok(body) = resp.json::<serde_json::Value>().await {
-                log::trace!("OpenCode /mcp response: {body}");
-                if let Some(rs) = body.get("roblox-studio") {
-                    let status_str = rs
-                        .get("status")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown");
-
-                    match status_str {
-                        "failed" => {
-                            let err = rs.get("error").and_then(|v| v.as_str()).map(String::from);
-                            return Ok(StudioStatusResult {
-                                status: "failed".into(),
-                                error: err,
-                            });
-                        }
-                        "disabled" => {
-                            return Ok(StudioStatusResult {
-                                status: "disabled".into(),
-                                error: None,
-                            });
-                        }
-                        "needs_auth" | "needs_client_registration" => {
-                            return Ok(StudioStatusResult {
-                                status: "needs_auth".into(),
-                                error: None,
-                            });
-                        }
-                        "connected" => {
-                            mcp_connected = true;
-                        }
-                        other => {
-                            log::warn!("Unknown MCP status: {other}");
-                        }
+    match fetch_mcp_status(oc_port, &workspace_str).await {
+        Ok(body) => {
+            if let Some(rs) = body.get(crate::studio_tools::MCP_SERVER_NAME) {
+                let status_str = rs
+                    .get("status")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown");
+
+                match status_str {
+                    "failed" => {
+                        let err = rs.get("error").and_then(|v| v.as_str()).map(String::from);
+                        return Ok(StudioStatusResult {
+                            status: "failed".into(),
+                            error: err,
+                        });
+                    }
+                    "disabled" => {
+                        return Ok(StudioStatusResult {
+                            status: "disabled".into(),
+                            error: None,
+                        });
+                    }
+                    "needs_auth" | "needs_client_registration" => {
+                        return Ok(StudioStatusResult {
+                            status: "needs_auth".into(),
+                            error: None,
+                        });
+                    }
+                    "connected" => {
+                        mcp_connected = true;
+                    }
+                    other => {
+                        log::warn!("Unknown MCP status: {other}");
                     }
-                } else {
-                    log::debug!("No 'roblox-studio' key in /mcp response");
                 }
+            } else {
+                log::debug!("No 'roblox-studio' key in /mcp response");
             }
         }
-        Ok(resp) => {
-            log::warn!("OpenCode /mcp returned HTTP {}", resp.status());
-        }
-        Err(e) => {
-            log::warn!("OpenCode /mcp request failed: {e}");
-        }
+        Err(e) => log::warn!("{e}"),
     }
 
     // Only check the health endpoint if OpenCode reports MCP as connected.
@@ -909,6 +1864,24 @@ pub async fn poll_studio_status(
         });
     }
 
+    // The frontend may have restarted the MCP server since startup; keep
+    // the registry pointing at the current launcher and server. Only ask
+    // the launcher again once a known PID has gone away.
+    let (launcher_pid, mcp_server_pid) = {
+        let s = state.lock().await;
+        (s.launcher_pid, s.mcp_server_pid)
+    };
+    let pids_stale = match (launcher_pid, mcp_server_pid) {
+        (Some(launcher), Some(server)) => {
+            !crate::process_registry::is_alive(launcher)
+                || !crate::process_registry::is_alive(server)
+        }
+        _ => true,
+    };
+    if pids_stale {
+        record_mcp_processes(state.inner(), control_port).await;
+    }
+
     // ── Step 2: poll the MCP bridge health endpoint ────────────────
     let health_url = format!("http://{LOOPBACK}:{mcp_port}/health");
     log::trace!("Checking MCP health at {health_url}");
@@ -923,6 +1896,14 @@ pub async fn poll_studio_status(
                 .get("pluginConnected")
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false);
+            if plugin_connected {
+                look_up_studio_place(state.inner(), &app, mcp_port).await;
+            } else {
+                let mut s = state.lock().await;
+                s.studio_place = None;
+                s.next_place_lookup = None;
+                s.place_lookup_failures = 0;
+            }
             Ok(StudioStatusResult {
                 status: if plugin_connected {
                     "connected"
@@ -951,16 +1932,143 @@ pub async fn poll_studio_status(
     }
 }
 
+/// How often the connected Studio is asked again which place it has open,
+/// so opening another place updates the studio prompt.
+const PLACE_RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Wait after the first failed place lookup; doubles per failure.
+const PLACE_RETRY_MIN: std::time::Duration = std::time::Duration::from_secs(10);
+
+const PLACE_RETRY_MAX: std::time::Duration = std::time::Duration::from_secs(300);
+
+fn place_retry_delay(failures: u32) -> std::time::Duration {
+    PLACE_RETRY_MIN
+        .saturating_mul(1 << failures.saturating_sub(1).min(8))
+        .min(PLACE_RETRY_MAX)
+}
+
+/// Ask the connected Studio which place it has open, in the background
+/// since bridge calls go through the plugin's polling loop. Runs when the
+/// plugin connects, then every `PLACE_RECHECK_INTERVAL`, backing off after
+/// failures. If the name differs from the one in the studio prompt,
+/// regenerate the config once the sessions are idle.
+async fn look_up_studio_place(state: &SharedOpenCodeState, app: &AppHandle, mcp_port: u16) {
+    {
+        let mut s = state.lock().await;
+        if s.place_lookup_running || s.next_place_lookup.is_some_and(|at| Instant::now() < at) {
+            return;
+        }
+        s.place_lookup_running = true;
+    }
+    let state = Arc::clone(state);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = crate::studio_bridge::place_info(mcp_port).await;
+        let changed = {
+            let mut s = state.lock().await;
+            s.place_lookup_running = false;
+            match result {
+                Ok(info) => {
+                    s.place_lookup_failures = 0;
+                    s.next_place_lookup = Some(Instant::now() + PLACE_RECHECK_INTERVAL);
+                    let changed = s.prompt_place.as_ref() != Some(&info.name);
+                    s.studio_place = Some(info.name);
+                    changed
+                }
+                Err(e) => {
+                    s.place_lookup_failures += 1;
+                    let delay = place_retry_delay(s.place_lookup_failures);
+                    s.next_place_lookup = Some(Instant::now() + delay);
+                    log::debug!(
+                        "Cannot look up the Studio place, retrying in {}s: {e}",
+                        delay.as_secs()
+                    );
+                    false
+                }
+            }
+        };
+        if changed {
+            reconfigure_when_idle(&state, &app, "Studio place name");
+        }
+    });
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct StudioStatusResult {
     pub status: String,
     pub error: Option<String>,
 }
 
+/// Status of every MCP server in the generated config: the bundled
+/// roblox-studio server plus the user's `mcp_servers`. Servers OpenCode
+/// doesn't report on (yet) are `unknown`.
+#[tauri::command]
+pub async fn poll_mcp_servers(
+    state: tauri::State<'_, SharedOpenCodeState>,
+) -> Result<Vec<McpServerStatus>, String> {
+    let mut names = vec![crate::studio_tools::MCP_SERVER_NAME.to_string()];
+    names.extend(crate::config::get().mcp_servers.into_keys());
+
+    let running_port = {
+        let s = state.lock().await;
+        // A degraded server may still answer; the watchdog decides when
+        // it is gone.
+        matches!(s.status, OpenCodeStatus::Running | OpenCodeStatus::Degraded).then_some(s.port)
+    };
+    let body = match running_port {
+        Some(port) => {
+            let workspace = crate::paths::workspace_dir()?;
+            match fetch_mcp_status(port, &workspace.to_string_lossy()).await {
+                Ok(body) => body,
+                Err(e) => {
+                    log::warn!("{e}");
+                    serde_json::Value::Null
+                }
+            }
+        }
+        // If OpenCode isn't running yet there's nothing to poll.
+        None => serde_json::Value::Null,
+    };
+
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let entry = body.get(&name);
+            let status = match entry.and_then(|e| e.get("status")).and_then(|v| v.as_str()) {
+                Some(s @ ("connected" | "failed" | "disabled")) => s,
+                Some("needs_auth" | "needs_client_registration") => "needs_auth",
+                Some(other) => {
+                    log::warn!("Unknown MCP status for {name}: {other}");
+                    "unknown"
+                }
+                None => "unknown",
+            };
+            McpServerStatus {
+                builtin: name == crate::studio_tools::MCP_SERVER_NAME,
+                error: entry
+                    .and_then(|e| e.get("error"))
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                status: status.into(),
+                name,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct McpServerStatus {
+    pub name: String,
+    /// `connected`, `failed`, `disabled`, `needs_auth` or `unknown`.
+    pub status: String,
+    pub error: Option<String>,
+    /// `true` for the bundled roblox-studio server.
+    pub builtin: bool,
+}
+
 /// Gracefully shut down the MCP server via the launcher's control endpoint.
 /// Called on app quit and before MCP restart to ensure clean process cleanup.
-pub async fn shutdown_mcp_server(mcp_port: u16) {
-    let control_port = mcp_port.wrapping_add(10);
+pub async fn shutdown_mcp_server(control_port: u16) {
     let url = format!("http://{LOOPBACK}:{control_port}/shutdown");
 
     match http_client().post(&url).send().await {
@@ -980,8 +2088,8 @@ pub async fn shutdown_mcp_server(mcp_port: u16) {
 /// Tauri command wrapper for shutdown_mcp_server.
 #[tauri::command]
 pub async fn shutdown_mcp(state: tauri::State<'_, SharedOpenCodeState>) -> Result<(), String> {
-    let mcp_port = state.lock().await.mcp_port;
-    shutdown_mcp_server(mcp_port).await;
+    let control_port = state.lock().await.control_port;
+    shutdown_mcp_server(control_port).await;
     Ok(())
 }
 
@@ -0,0 +1,684 @@
+//! Generation of the `opencode.json` the bundled OpenCode server reads.
+//!
+//! OpenCode ignores `OPENCODE_CONFIG_CONTENT`; it loads its config from
+//! `{XDG_CONFIG_HOME}/opencode/opencode.json`, and BloxBot points
+//! `XDG_CONFIG_HOME` at an isolated directory under `~/BloxBot/.opencode/`.
+//! The file is regenerated on every start and on `reconfigure`.
+//!
+//! The parts of OpenCode's schema BloxBot writes are modelled by
+//! `OpenCodeConfig`. Power users can tweak anything else (or override what
+//! we generate) in `opencode.override.json` in the workspace root
+//! (`~/BloxBot` by default). It applies to every project and is deep-merged
+//! on top as a JSON merge patch: objects merge key by key, other values
+//! replace, and `null` removes a key. If the merged result no longer
+//! passes validation, the override is ignored and the generated config is
+//! written as-is.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{
+    AgentMode, CustomAgent, LocalProvider, McpServerConfig, McpTransport, ToolAction,
+};
+use crate::opencode::LOOPBACK;
+
+pub const OVERRIDE_FILENAME: &str = "opencode.override.json";
+
+// ── Schema ──────────────────────────────────────────────────────────────
+
+/// The subset of OpenCode's config schema that BloxBot generates.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenCodeConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugin: Vec<String>,
+
+    /// Files appended to every agent's system prompt.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructions: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mcp: BTreeMap<String, McpEntry>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_agent: Option<String>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub agent: BTreeMap<String, AgentEntry>,
+
+    /// OpenCode tool ID → action, applied to every agent.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub permission: BTreeMap<String, PermissionRule>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provider: BTreeMap<String, ProviderEntry>,
+}
+
+/// An entry of the `mcp` map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum McpEntry {
+    Local {
+        /// Executable followed by its arguments.
+        command: Vec<String>,
+        #[serde(default = "default_true")]
+        enabled: bool,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        environment: BTreeMap<String, String>,
+    },
+    Remote {
+        url: String,
+        #[serde(default = "default_true")]
+        enabled: bool,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// An entry of the `agent` map.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<AgentMode>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+
+    /// Tool ID or glob → enabled. The most specific pattern wins.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, bool>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub permission: BTreeMap<String, PermissionRule>,
+}
+
+/// A `permission` value: one action for every call of the tool, or an
+/// action per argument pattern, e.g. `"bash": {"git *": "allow", "*": "ask"}`.
+/// BloxBot only generates the first form; the second comes from overrides.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PermissionRule {
+    Action(ToolAction),
+    Patterns(BTreeMap<String, ToolAction>),
+}
+
+impl From<ToolAction> for PermissionRule {
+    fn from(action: ToolAction) -> Self {
+        Self::Action(action)
+    }
+}
+
+/// An entry of the `provider` map.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderEntry {
+    /// AI SDK package implementing the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub npm: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    pub options: ProviderOptions,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, ModelEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderOptions {
+    #[serde(rename = "baseURL", default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+
+    #[serde(rename = "apiKey", default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<ModelLimit>,
+}
+
+/// Token limits. OpenCode requires both fields together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelLimit {
+    pub context: u32,
+    pub output: u32,
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+impl OpenCodeConfig {
+    /// Check the invariants OpenCode relies on but serde can't express.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(plugin) = self.plugin.iter().find(|p| p.trim().is_empty()) {
+            return Err(format!("plugin entry \"{plugin}\" is empty"));
+        }
+        for (name, entry) in &self.mcp {
+            match entry {
+                McpEntry::Local { command, .. } => {
+                    if command.first().is_none_or(|c| c.trim().is_empty()) {
+                        return Err(format!("mcp.{name}: command must not be empty"));
+                    }
+                }
+                McpEntry::Remote { url, .. } => {
+                    if !is_http_url(url) {
+                        return Err(format!(
+                            "mcp.{name}: url must start with http:// or https://, got \"{url}\""
+                        ));
+                    }
+                }
+            }
+        }
+        if let Some(default) = &self.default_agent {
+            if !self.agent.contains_key(default) && !crate::agents::is_builtin(default) {
+                return Err(format!("default_agent \"{default}\" is not a known agent"));
+            }
+        }
+        for (name, agent) in &self.agent {
+            if let Some(t) = agent.temperature {
+                if !(0.0..=2.0).contains(&t) {
+                    return Err(format!(
+                        "agent.{name}: temperature must be between 0 and 2, got {t}"
+                    ));
+                }
+            }
+        }
+        for (id, provider) in &self.provider {
+            if let Some(url) = &provider.options.base_url {
+                if !is_http_url(url) {
+                    return Err(format!(
+                        "provider.{id}: baseURL must start with http:// or https://, got \"{url}\""
+                    ));
+                }
+            }
+            for (model, entry) in &provider.models {
+                if entry.limit.is_some_and(|l| l.context == 0 || l.output == 0) {
+                    return Err(format!(
+                        "provider.{id}.models.{model}: limits must be positive"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ── Generation ──────────────────────────────────────────────────────────
+
+/// Values that vary between runs and end up in the generated config.
+pub struct ConfigInputs<'a> {
+    /// `node` / `node.exe`, resolved through the sidecar's `PATH`.
+    pub node_cmd: &'a str,
+    /// Absolute path of the bundled MCP launcher script.
+    pub mcp_entry: &'a str,
+    pub mcp_port: u16,
+    pub control_port: u16,
+    /// Rendered system prompt of the `studio` agent (see `prompts`).
+    pub studio_prompt: &'a str,
+    /// User-defined agents merged next to the built-in ones.
+    pub custom_agents: &'a [CustomAgent],
+    /// Per-tool allow/ask/deny rules for the roblox-studio MCP tools.
+    pub tool_policy: &'a BTreeMap<String, ToolAction>,
+    /// User-defined MCP servers merged next to roblox-studio.
+    pub mcp_servers: &'a BTreeMap<String, McpServerConfig>,
+    /// The sidecar's isolated `PATH`, passed on to local MCP servers.
+    pub path: &'a str,
+    /// Pinned plugins already present in the isolated cache.
+    pub plugins: &'a [String],
+    /// OpenAI-compatible local model servers.
+    pub local_providers: &'a [LocalProvider],
+    /// Project instruction files (see `instructions`).
+    pub instructions: &'a [String],
+}
+
+/// Build the full OpenCode config.
+pub fn generate(inputs: &ConfigInputs) -> OpenCodeConfig {
+    let mut config = OpenCodeConfig {
+        plugin: inputs.plugins.to_vec(),
+        instructions: inputs.instructions.to_vec(),
+        default_agent: Some("studio".to_string()),
+        permission: crate::studio_tools::permission_config(inputs.tool_policy),
+        provider: crate::providers::provider_config(inputs.local_providers),
+        ..Default::default()
+    };
+
+    config.mcp.insert(
+        crate::studio_tools::MCP_SERVER_NAME.to_string(),
+        McpEntry::Local {
+            command: vec![inputs.node_cmd.to_string(), inputs.mcp_entry.to_string()],
+            enabled: true,
+            environment: BTreeMap::from([
+                ("ROBLOX_STUDIO_HOST".to_string(), LOOPBACK.to_string()),
+                (
+                    "ROBLOX_STUDIO_PORT".to_string(),
+                    inputs.mcp_port.to_string(),
+                ),
+                (
+                    "BLOXBOT_CONTROL_PORT".to_string(),
+                    inputs.control_port.to_string(),
+                ),
+            ]),
+        },
+    );
+    merge_mcp_servers(&mut config.mcp, inputs.mcp_servers, inputs.path);
+
+    config.agent.insert(
+        "build".to_string(),
+        AgentEntry {
+            description: Some("Executes tools based on the conversation".to_string()),
+            ..Default::default()
+        },
+    );
+    config.agent.insert(
+        "studio".to_string(),
+        AgentEntry {
+            mode: Some(AgentMode::Primary),
+            description: Some("Roblox Studio development assistant".to_string()),
+            prompt: Some(inputs.studio_prompt.to_string()),
+            ..Default::default()
+        },
+    );
+    config.agent.insert(
+        crate::agents::READ_ONLY_AGENT.to_string(),
+        crate::agents::read_only_agent(inputs.tool_policy),
+    );
+    crate::agents::merge_custom(&mut config.agent, inputs.custom_agents);
+
+    config
+}
+
+/// Add the user's MCP servers to the generated `mcp` map. Names that are
+/// already taken are skipped; validation normally prevents that.
+fn merge_mcp_servers(
+    mcp: &mut BTreeMap<String, McpEntry>,
+    servers: &BTreeMap<String, McpServerConfig>,
+    path: &str,
+) {
+    for (name, server) in servers {
+        if mcp.contains_key(name) {
+            log::warn!("MCP server \"{name}\" collides with a built-in server; skipping it");
+            continue;
+        }
+        let entry = match &server.transport {
+            McpTransport::Local { command, args, env } => {
+                let mut environment = env.clone();
+                environment.insert("PATH".to_string(), path.to_string());
+                McpEntry::Local {
+                    command: std::iter::once(command.clone())
+                        .chain(args.iter().cloned())
+                        .collect(),
+                    enabled: server.enabled,
+                    environment,
+                }
+            }
+            McpTransport::Remote { url, headers } => McpEntry::Remote {
+                url: url.clone(),
+                enabled: server.enabled,
+                headers: headers.clone(),
+            },
+        };
+        mcp.insert(name.clone(), entry);
+    }
+}
+
+// ── User override ───────────────────────────────────────────────────────
+
+/// The generated config after the user override, as written to disk.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfig {
+    pub config: Value,
+    pub override_path: String,
+    /// `true` if an override file exists and was merged in.
+    pub override_applied: bool,
+    /// Why an existing override was ignored.
+    pub override_error: Option<String>,
+}
+
+static LAST_EFFECTIVE: Mutex<Option<EffectiveConfig>> = Mutex::new(None);
+
+/// In the workspace root rather than a project directory, so one override
+/// covers all projects and agents working in a project can't edit it.
+fn override_path() -> Result<PathBuf, String> {
+    Ok(crate::paths::workspace_root()?.join(OVERRIDE_FILENAME))
+}
+
+/// RFC 7396 JSON merge patch: objects merge recursively, `null` removes a
+/// key, anything else replaces the target.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Merge the override file at `path` into `generated` and validate the
+/// result. Keys outside `OpenCodeConfig` are passed through unchecked.
+fn apply_override(generated: &Value, path: &Path) -> Result<Option<Value>, String> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    let patch: Value = serde_json::from_slice(&bytes)
+        .map_err(|e| format!("{} is not valid JSON: {e}", path.display()))?;
+    if !patch.is_object() {
+        return Err(format!("{} must contain a JSON object", path.display()));
+    }
+
+    let mut merged = generated.clone();
+    merge_patch(&mut merged, &patch);
+    let typed: OpenCodeConfig = serde_json::from_value(merged.clone())
+        .map_err(|e| format!("Invalid config after applying {}: {e}", path.display()))?;
+    typed
+        .validate()
+        .map_err(|e| format!("Invalid config after applying {}: {e}", path.display()))?;
+    Ok(Some(merged))
+}
+
+/// Validate `config`, merge the user override and return the result.
+pub fn resolve(config: &OpenCodeConfig) -> Result<EffectiveConfig, String> {
+    config.validate()?;
+    let generated = serde_json::to_value(config)
+        .map_err(|e| format!("Failed to serialize OpenCode config: {e}"))?;
+    let path = override_path()?;
+    let (config, override_applied, override_error) = match apply_override(&generated, &path) {
+        Ok(Some(merged)) => {
+            log::info!("Applied OpenCode config override {}", path.display());
+            (merged, true, None)
+        }
+        Ok(None) => (generated, false, None),
+        Err(e) => {
+            log::warn!("Ignoring OpenCode config override: {e}");
+            (generated, false, Some(e))
+        }
+    };
+    Ok(EffectiveConfig {
+        config,
+        override_path: path.to_string_lossy().to_string(),
+        override_applied,
+        override_error,
+    })
+}
+
+/// `config` as pretty JSON with provider API keys and remote MCP headers
+/// masked, for logging.
+fn redacted(config: &Value) -> String {
+    let mut config = config.clone();
+    let mask = |value: &mut Value| *value = Value::String("<redacted>".to_string());
+    if let Some(providers) = config.get_mut("provider").and_then(Value::as_object_mut) {
+        for provider in providers.values_mut() {
+            if let Some(key) = provider.pointer_mut("/options/apiKey") {
+                mask(key);
+            }
+        }
+    }
+    if let Some(servers) = config.get_mut("mcp").and_then(Value::as_object_mut) {
+        for server in servers.values_mut() {
+            if let Some(headers) = server.get_mut("headers").and_then(Value::as_object_mut) {
+                headers.values_mut().for_each(mask);
+            }
+        }
+    }
+    serde_json::to_string_pretty(&config).unwrap_or_default()
+}
+
+/// Resolve `config`, write the effective result to `path` (creating parent
+/// directories) and remember it for `get_effective_opencode_config`.
+pub fn write(path: &Path, config: &OpenCodeConfig) -> Result<(), String> {
+    let effective = resolve(config)?;
+    let content = serde_json::to_string_pretty(&effective.config)
+        .map_err(|e| format!("Failed to serialize OpenCode config: {e}"))?;
+    log::debug!("Config: {}", redacted(&effective.config));
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {e}"))?;
+    }
+    std::fs::write(path, &content).map_err(|e| format!("Failed to write OpenCode config: {e}"))?;
+    log::info!("Wrote OpenCode config to {}", path.display());
+    *LAST_EFFECTIVE.lock().unwrap() = Some(effective);
+    Ok(())
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+/// The config most recently written for the sidecar, override included.
+#[tauri::command]
+pub fn get_effective_opencode_config() -> Result<EffectiveConfig, String> {
+    LAST_EFFECTIVE
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "The OpenCode config has not been generated yet".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use serde_json::json;
+
+    fn inputs<'a>(tool_policy: &'a BTreeMap<String, ToolAction>) -> ConfigInputs<'a> {
+        static NO_SERVERS: BTreeMap<String, McpServerConfig> = BTreeMap::new();
+        ConfigInputs {
+            node_cmd: "node",
+            mcp_entry: "/app/launcher/index.js",
+            mcp_port: 44755,
+            control_port: 44756,
+            studio_prompt: "You are BloxBot.",
+            custom_agents: &[],
+            tool_policy,
+            mcp_servers: &NO_SERVERS,
+            path: "/app/node/bin",
+            plugins: &[],
+            local_providers: &[],
+            instructions: &[],
+        }
+    }
+
+    /// An override file in a temp directory that lives as long as the
+    /// returned guard.
+    fn temp_file(contents: &str) -> (TempDir, PathBuf) {
+        let dir = TempDir::new("opencode-config");
+        let path = dir.path().join(OVERRIDE_FILENAME);
+        std::fs::write(&path, contents).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn generate_builds_a_valid_config() {
+        let config = generate(&inputs(&BTreeMap::new()));
+        config.validate().unwrap();
+
+        assert_eq!(config.default_agent.as_deref(), Some("studio"));
+        for agent in ["build", "studio", crate::agents::READ_ONLY_AGENT] {
+            assert!(config.agent.contains_key(agent), "{agent}");
+        }
+        assert_eq!(
+            config.agent["studio"].prompt.as_deref(),
+            Some("You are BloxBot.")
+        );
+        let Some(McpEntry::Local {
+            command,
+            environment,
+            ..
+        }) = config.mcp.get(crate::studio_tools::MCP_SERVER_NAME)
+        else {
+            panic!("roblox-studio must be a local MCP server");
+        };
+        assert_eq!(command, &["node", "/app/launcher/index.js"]);
+        assert_eq!(environment["ROBLOX_STUDIO_PORT"], "44755");
+        assert_eq!(environment["BLOXBOT_CONTROL_PORT"], "44756");
+    }
+
+    #[test]
+    fn generate_applies_the_tool_policy() {
+        let policy = BTreeMap::from([("execute_luau".to_string(), ToolAction::Deny)]);
+        let config = generate(&inputs(&policy));
+        assert_eq!(
+            config.permission.get("roblox-studio_execute_luau"),
+            Some(&PermissionRule::Action(ToolAction::Deny))
+        );
+    }
+
+    #[test]
+    fn validate_rejects_broken_configs() {
+        type Breakage = fn(&mut OpenCodeConfig);
+        let cases: [(&str, Breakage); 6] = [
+            ("plugin entry", |c| c.plugin.push(" ".to_string())),
+            ("command must not be empty", |c| {
+                c.mcp.insert(
+                    "local".to_string(),
+                    McpEntry::Local {
+                        command: Vec::new(),
+                        enabled: true,
+                        environment: BTreeMap::new(),
+                    },
+                );
+            }),
+            ("url must start with", |c| {
+                c.mcp.insert(
+                    "remote".to_string(),
+                    McpEntry::Remote {
+                        url: "ftp://example.com".to_string(),
+                        enabled: true,
+                        headers: BTreeMap::new(),
+                    },
+                );
+            }),
+            ("not a known agent", |c| {
+                c.default_agent = Some("nobody".to_string());
+            }),
+            ("temperature must be between", |c| {
+                c.agent.insert(
+                    "hot".to_string(),
+                    AgentEntry {
+                        temperature: Some(2.5),
+                        ..Default::default()
+                    },
+                );
+            }),
+            ("limits must be positive", |c| {
+                c.provider.insert(
+                    "local".to_string(),
+                    ProviderEntry {
+                        models: BTreeMap::from([(
+                            "m".to_string(),
+                            ModelEntry {
+                                name: None,
+                                limit: Some(ModelLimit {
+                                    context: 0,
+                                    output: 0,
+                                }),
+                            },
+                        )]),
+                        ..Default::default()
+                    },
+                );
+            }),
+        ];
+        for (expected, breakage) in cases {
+            let mut config = generate(&inputs(&BTreeMap::new()));
+            breakage(&mut config);
+            let err = config.validate().unwrap_err();
+            assert!(
+                err.contains(expected),
+                "expected \"{expected}\", got \"{err}\""
+            );
+        }
+    }
+
+    #[test]
+    fn merge_patch_follows_rfc_7396() {
+        let mut target = json!({
+            "keep": 1,
+            "remove": true,
+            "nested": { "a": 1, "b": 2 },
+            "replace": { "x": 1 },
+        });
+        merge_patch(
+            &mut target,
+            &json!({
+                "remove": null,
+                "nested": { "b": 3, "c": 4 },
+                "replace": [1, 2],
+                "added": "new",
+            }),
+        );
+        assert_eq!(
+            target,
+            json!({
+                "keep": 1,
+                "nested": { "a": 1, "b": 3, "c": 4 },
+                "replace": [1, 2],
+                "added": "new",
+            })
+        );
+    }
+
+    #[test]
+    fn override_is_merged_and_accepts_nested_permissions() {
+        let generated = serde_json::to_value(generate(&inputs(&BTreeMap::new()))).unwrap();
+        let (_dir, path) = temp_file(
+            r#"{ "permission": { "bash": { "git *": "allow", "*": "ask" } }, "theme": "dark" }"#,
+        );
+        let merged = apply_override(&generated, &path).unwrap().unwrap();
+        assert_eq!(merged["permission"]["bash"]["git *"], "allow");
+        assert_eq!(merged["theme"], "dark");
+        assert_eq!(merged["default_agent"], "studio");
+    }
+
+    #[test]
+    fn invalid_override_is_rejected() {
+        let generated = serde_json::to_value(generate(&inputs(&BTreeMap::new()))).unwrap();
+        let (_dir, path) = temp_file(r#"{ "agent": { "studio": { "temperature": 5 } } }"#);
+        let err = apply_override(&generated, &path).unwrap_err();
+        assert!(err.contains("temperature"), "{err}");
+
+        let (_dir, path) = temp_file("{ nope");
+        assert!(apply_override(&generated, &path).is_err());
+    }
+
+    #[test]
+    fn missing_override_is_not_an_error() {
+        let generated = json!({});
+        let dir = TempDir::new("opencode-config-missing");
+        let path = dir.path().join(OVERRIDE_FILENAME);
+        assert_eq!(apply_override(&generated, &path), Ok(None));
+    }
+}
@@ -127,17 +127,82 @@ pub fn bundled_launcher_dir() -> Result<PathBuf, String> {
     ))
 }
 
+/// Returns the path to the bundled OpenCode plugins directory, which holds
+/// `plugins.tgz` (see `plugins`).
+///
+/// Production macOS: `<App>/Contents/Resources/resources/plugins/`
+/// Production Windows: `<App>/resources/plugins/`
+/// Dev: `src-tauri/resources/plugins/`
+pub fn bundled_plugins_dir() -> Result<PathBuf, String> {
+    let sidecar = sidecar_dir()?;
+
+    #[cfg(target_os = "macos")]
+    let prod_path = sidecar
+        .parent()
+        .map(|p| p.join("Resources").join("resources").join("plugins"))
+        .unwrap_or_default();
+    #[cfg(not(target_os = "macos"))]
+    let prod_path = sidecar.join("resources").join("plugins");
+
+    if prod_path.join("plugins.tgz").exists() {
+        return Ok(prod_path);
+    }
+
+    let dev_path = sidecar
+        .parent()
+        .and_then(|p| p.parent())
+        .map(|p| p.join("resources").join("plugins"))
+        .unwrap_or_default();
+
+    if dev_path.join("plugins.tgz").exists() {
+        return Ok(dev_path);
+    }
+
+    Err(format!(
+        "Bundled plugins not found. Checked:\n  {}\n  {}",
+        prod_path.display(),
+        dev_path.display()
+    ))
+}
+
 /// Returns the path to the bundled OpenCode sidecar binary.
 #[allow(dead_code)]
 pub fn bundled_opencode_path() -> Result<PathBuf, String> {
     sidecar_path("opencode")
 }
 
-/// Returns the BloxBot workspace directory (`~/BloxBot`), creating it if
-/// it does not exist. This is where OpenCode sessions operate.
-pub fn workspace_dir() -> Result<PathBuf, String> {
+/// Environment variable that overrides the workspace root.
+pub const WORKSPACE_ROOT_ENV: &str = "BLOXBOT_HOME";
+
+/// `~/BloxBot`, used when neither `BLOXBOT_HOME` nor
+/// `AppConfig::workspace_root` is set.
+pub fn default_workspace_root() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    let workspace = home.join("BloxBot");
+    Ok(home.join("BloxBot"))
+}
+
+/// The `BLOXBOT_HOME` override, if set to a non-empty value.
+pub fn workspace_root_from_env() -> Option<PathBuf> {
+    std::env::var_os(WORKSPACE_ROOT_ENV)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Returns the BloxBot workspace root, creating it if it does not exist.
+/// It contains the default project in `default/`, the managed projects
+/// under `projects/` and the default project's OpenCode data (see
+/// `projects`).
+///
+/// Resolved from `BLOXBOT_HOME`, then `AppConfig::workspace_root`, then
+/// `~/BloxBot`.
+pub fn workspace_root() -> Result<PathBuf, String> {
+    let workspace = match workspace_root_from_env() {
+        Some(dir) => dir,
+        None => match crate::config::get().workspace_root {
+            Some(dir) => dir,
+            None => default_workspace_root()?,
+        },
+    };
     if !workspace.exists() {
         std::fs::create_dir_all(&workspace)
             .map_err(|e| format!("Failed to create BloxBot workspace: {e}"))?;
@@ -145,6 +210,12 @@ pub fn workspace_dir() -> Result<PathBuf, String> {
     Ok(workspace)
 }
 
+/// Returns the current project's directory, creating it if it does not
+/// exist. This is where OpenCode sessions operate.
+pub fn workspace_dir() -> Result<PathBuf, String> {
+    crate::projects::current_dir()
+}
+
 // ── Studio plugin ────────────────────────────────────────────────────────
 
 const PLUGIN_FILENAME: &str = "BloxBotPlugin.rbxmx";
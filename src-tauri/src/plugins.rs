@@ -0,0 +1,483 @@
+//! OpenCode plugins.
+//!
+//! OpenCode installs each `plugin` entry into
+//! `{XDG_CACHE_HOME}/opencode/node_modules` with Bun, and skips the install
+//! when `{XDG_CACHE_HOME}/opencode/package.json` already lists that exact
+//! version. BloxBot builds on that so the sidecar never has to reach npm:
+//!
+//! - Every plugin is pinned (`name@1.2.3`). `AppConfig::plugins` overrides
+//!   the defaults, which are the dependencies of the bundled
+//!   `resources/plugins/package.json`.
+//! - CI installs those defaults and packs their `node_modules` into
+//!   `resources/plugins/plugins.tgz`. Before the sidecar starts, the
+//!   tarball is unpacked into the isolated cache and `package.json` is
+//!   updated to match.
+//! - Plugins that are not in the bundle are installed into the same cache
+//!   with the bundled npm.
+//!
+//! A plugin that still isn't in the cache at its pinned version afterwards
+//! is left out of `opencode.json` with a warning. OpenCode fails the whole
+//! instance when a plugin can't be installed, so dropping it keeps chat and
+//! Studio working.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use serde_json::{Map, Value};
+
+const BUNDLE_MANIFEST: &str = include_str!("../resources/plugins/package.json");
+const BUNDLE_ARCHIVE: &str = "plugins.tgz";
+
+/// `prepare` runs on the startup path. Without a network npm keeps
+/// retrying for minutes, so give up well before that.
+const NPM_TIMEOUT: Duration = Duration::from_secs(60);
+
+// ── Specs ───────────────────────────────────────────────────────────────
+
+/// Split `name@version`, allowing scoped names (`@scope/name@1.0.0`). The
+/// version must be exact: ranges and dist-tags like `latest` are rejected.
+pub fn parse_spec(spec: &str) -> Result<(&str, &str), String> {
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.')
+            })
+    };
+    let (name, version) = match spec.rfind('@') {
+        Some(at) if at > 0 => (&spec[..at], &spec[at + 1..]),
+        _ => {
+            return Err(format!(
+                "Plugin \"{spec}\" must be pinned as \"name@version\""
+            ))
+        }
+    };
+    let valid_name = match name.strip_prefix('@') {
+        Some(scoped) => scoped
+            .split_once('/')
+            .is_some_and(|(scope, package)| valid_part(scope) && valid_part(package)),
+        None => valid_part(name),
+    };
+    if !valid_name {
+        return Err(format!("Plugin \"{spec}\" has an invalid package name"));
+    }
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let parts: Vec<&str> = core.split('.').collect();
+    if parts.len() != 3
+        || parts
+            .iter()
+            .any(|p| p.is_empty() || !p.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Err(format!(
+            "Plugin \"{spec}\" must use an exact version like 1.2.3, got \"{version}\""
+        ));
+    }
+    Ok((name, version))
+}
+
+/// Plugins pinned by the bundled manifest, as `name -> version`.
+fn bundled_versions() -> BTreeMap<String, String> {
+    serde_json::from_str::<Value>(BUNDLE_MANIFEST)
+        .ok()
+        .and_then(|manifest| manifest.get("dependencies").cloned())
+        .and_then(|deps| serde_json::from_value(deps).ok())
+        .unwrap_or_default()
+}
+
+/// The default `plugin` list: everything in the bundle.
+pub fn default_plugins() -> Vec<String> {
+    bundled_versions()
+        .into_iter()
+        .map(|(name, version)| format!("{name}@{version}"))
+        .collect()
+}
+
+/// The plugins to load: `AppConfig::plugins`, or the bundled defaults.
+pub fn configured() -> Vec<String> {
+    crate::config::get().plugins.unwrap_or_else(default_plugins)
+}
+
+// ── Cache ───────────────────────────────────────────────────────────────
+
+/// OpenCode's own package manifest for its plugin cache.
+fn read_cache_manifest(cache_dir: &Path) -> Map<String, Value> {
+    std::fs::read(cache_dir.join("package.json"))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+        .and_then(|v| v.as_object().cloned())
+        .unwrap_or_default()
+}
+
+fn write_cache_manifest(cache_dir: &Path, manifest: &Map<String, Value>) -> Result<(), String> {
+    let path = cache_dir.join("package.json");
+    let content = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize {}: {e}", path.display()))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Version of `name` actually present in the cache's `node_modules`.
+fn installed_version(cache_dir: &Path, name: &str) -> Option<String> {
+    let mut path = cache_dir.join("node_modules");
+    path.extend(name.split('/'));
+    let bytes = std::fs::read(path.join("package.json")).ok()?;
+    let manifest: Value = serde_json::from_slice(&bytes).ok()?;
+    manifest.get("version")?.as_str().map(str::to_string)
+}
+
+fn is_ready(cache_dir: &Path, deps: &Map<String, Value>, name: &str, version: &str) -> bool {
+    deps.get(name).and_then(Value::as_str) == Some(version)
+        && installed_version(cache_dir, name).as_deref() == Some(version)
+}
+
+/// The bundled `node_modules` tarball.
+pub fn bundle_archive() -> Result<PathBuf, String> {
+    Ok(crate::paths::bundled_plugins_dir()?.join(BUNDLE_ARCHIVE))
+}
+
+/// Unpack the bundled `node_modules` tarball at `archive` into `cache_dir`.
+fn unpack_bundle(archive: &Path, cache_dir: &Path) -> Result<(), String> {
+    let file = std::fs::File::open(archive)
+        .map_err(|e| format!("Failed to open {}: {e}", archive.display()))?;
+    tar::Archive::new(flate2::read::GzDecoder::new(file))
+        .unpack(cache_dir)
+        .map_err(|e| format!("Failed to unpack {}: {e}", archive.display()))?;
+    log::info!("Unpacked bundled plugins into {}", cache_dir.display());
+    Ok(())
+}
+
+/// Install `specs` into `cache_dir` with the bundled npm. Killed after
+/// `NPM_TIMEOUT`.
+fn npm_install(
+    cache_dir: &Path,
+    npm: &Path,
+    path_env: &str,
+    specs: &[String],
+) -> Result<(), String> {
+    log::info!("Installing plugins with npm: {}", specs.join(", "));
+    let mut child = std::process::Command::new(npm)
+        .arg("install")
+        .args([
+            "--save-exact",
+            "--no-package-lock",
+            "--no-audit",
+            "--no-fund",
+        ])
+        .arg("--prefix")
+        .arg(cache_dir)
+        .args(specs)
+        .env("PATH", path_env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {e}", npm.display()))?;
+
+    // Drain stderr on a thread so a chatty npm can't block on a full pipe.
+    let stderr = child.stderr.take().map(|mut pipe| {
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            let _ = pipe.read_to_string(&mut text);
+            text
+        })
+    });
+    let deadline = Instant::now() + NPM_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "npm install did not finish within {}s",
+                    NPM_TIMEOUT.as_secs()
+                ));
+            }
+            Err(e) => return Err(format!("Failed to wait for npm: {e}")),
+        }
+    };
+    if status.success() {
+        Ok(())
+    } else {
+        let stderr = stderr
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        Err(format!(
+            "npm install exited with {status}: {}",
+            stderr.trim()
+        ))
+    }
+}
+
+/// Make `plugins` available in OpenCode's cache at `cache_dir` and return
+/// the ones that are, in config order. `bundle` is the bundled tarball (see
+/// `bundle_archive`), if found. Blocking: may unpack the bundle and run
+/// npm.
+pub fn prepare(
+    cache_dir: &Path,
+    bundle: Result<&Path, &str>,
+    npm: &Path,
+    path_env: &str,
+    plugins: &[String],
+) -> Vec<String> {
+    let specs: Vec<(&str, &str)> = plugins
+        .iter()
+        .filter_map(|spec| match parse_spec(spec) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                log::warn!("Skipping plugin: {e}");
+                None
+            }
+        })
+        .collect();
+    if specs.is_empty() {
+        return Vec::new();
+    }
+    if let Err(e) = std::fs::create_dir_all(cache_dir) {
+        log::warn!("Failed to create {}: {e}", cache_dir.display());
+    }
+
+    let mut manifest = read_cache_manifest(cache_dir);
+    let mut deps = manifest
+        .get("dependencies")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    let missing = |deps: &Map<String, Value>| -> Vec<(&str, &str)> {
+        specs
+            .iter()
+            .copied()
+            .filter(|(name, version)| !is_ready(cache_dir, deps, name, version))
+            .collect()
+    };
+
+    // ── Bundled plugins ──────────────────────────────────────────────
+    let bundled = bundled_versions();
+    let from_bundle: Vec<(&str, &str)> = missing(&deps)
+        .into_iter()
+        .filter(|(name, version)| bundled.get(*name).map(String::as_str) == Some(*version))
+        .collect();
+    if !from_bundle.is_empty() {
+        match bundle
+            .map_err(str::to_string)
+            .and_then(|archive| unpack_bundle(archive, cache_dir))
+        {
+            Ok(()) => {
+                for (name, version) in from_bundle {
+                    deps.insert(name.to_string(), Value::String(version.to_string()));
+                }
+                manifest.insert("dependencies".to_string(), Value::Object(deps.clone()));
+                if let Err(e) = write_cache_manifest(cache_dir, &manifest) {
+                    log::warn!("{e}");
+                }
+            }
+            Err(e) => log::warn!("Bundled plugins unavailable: {e}"),
+        }
+    }
+
+    // ── Everything else ──────────────────────────────────────────────
+    let to_install: Vec<String> = missing(&deps)
+        .into_iter()
+        .map(|(name, version)| format!("{name}@{version}"))
+        .collect();
+    if !to_install.is_empty() {
+        if let Err(e) = npm_install(cache_dir, npm, path_env, &to_install) {
+            log::warn!("Plugin install failed: {e}");
+        }
+        deps = read_cache_manifest(cache_dir)
+            .get("dependencies")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+    }
+
+    specs
+        .into_iter()
+        .filter_map(|(name, version)| {
+            if is_ready(cache_dir, &deps, name, version) {
+                Some(format!("{name}@{version}"))
+            } else {
+                log::warn!(
+                    "Plugin {name}@{version} is not available offline; leaving it out of the OpenCode config"
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// What `prepare` returned for a plugin list and cache, so a reload that
+/// changes neither can skip it.
+#[derive(Debug, Clone)]
+pub struct Prepared {
+    pub cache_dir: PathBuf,
+    pub plugins: Vec<String>,
+    pub ready: Vec<String>,
+}
+
+/// Where OpenCode keeps its plugin cache for a given `XDG_CACHE_HOME`.
+pub fn cache_dir(xdg_cache: &Path) -> PathBuf {
+    xdg_cache.join("opencode")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// Put `name` at `version` into a cache the way an install would.
+    fn install(cache_dir: &Path, name: &str, version: &str) {
+        let mut dir = cache_dir.join("node_modules");
+        dir.extend(name.split('/'));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("package.json"),
+            serde_json::json!({ "name": name, "version": version }).to_string(),
+        )
+        .unwrap();
+        let mut manifest = read_cache_manifest(cache_dir);
+        let deps = manifest
+            .entry("dependencies")
+            .or_insert_with(|| Value::Object(Map::new()));
+        deps.as_object_mut()
+            .unwrap()
+            .insert(name.to_string(), Value::String(version.to_string()));
+        write_cache_manifest(cache_dir, &manifest).unwrap();
+    }
+
+    fn no_npm() -> PathBuf {
+        PathBuf::from("/nonexistent/bloxbot/npm")
+    }
+
+    #[test]
+    fn parse_spec_accepts_pinned_and_scoped_names() {
+        assert_eq!(
+            parse_spec("opencode-gemini-auth@1.3.7"),
+            Ok(("opencode-gemini-auth", "1.3.7"))
+        );
+        assert_eq!(
+            parse_spec("@acme/opencode-plugin@0.10.2"),
+            Ok(("@acme/opencode-plugin", "0.10.2"))
+        );
+        assert_eq!(
+            parse_spec("plugin.js@2.0.0-beta.1+build.5"),
+            Ok(("plugin.js", "2.0.0-beta.1+build.5"))
+        );
+    }
+
+    #[test]
+    fn parse_spec_rejects_ranges_and_tags() {
+        for spec in [
+            "plugin",
+            "plugin@",
+            "plugin@latest",
+            "plugin@^1.2.3",
+            "plugin@~1.2.3",
+            "plugin@1.2",
+            "plugin@1.2.x",
+            "@acme/plugin",
+            "@acme/plugin@next",
+        ] {
+            let err = parse_spec(spec).unwrap_err();
+            assert!(err.contains("version"), "{spec}: {err}");
+        }
+    }
+
+    #[test]
+    fn parse_spec_allows_at_only_as_the_scope_marker() {
+        for spec in [
+            "plu@gin@1.0.0",
+            "@acme@evil/plugin@1.0.0",
+            "@acme/@plugin@1.0.0",
+            "@acme@1.0.0",
+            "@/plugin@1.0.0",
+            "acme/plugin@1.0.0",
+            "@acme/plugin/extra@1.0.0",
+            "Plugin@1.0.0",
+        ] {
+            let err = parse_spec(spec).unwrap_err();
+            assert!(err.contains("invalid package name"), "{spec}: {err}");
+        }
+    }
+
+    #[test]
+    fn cached_plugins_are_used_without_npm() {
+        let temp = TempDir::new("plugins-cached");
+        let cache_dir = temp.path();
+        install(cache_dir, "@acme/plugin", "1.0.0");
+
+        let ready = prepare(
+            cache_dir,
+            Err("no bundle"),
+            &no_npm(),
+            "",
+            &["@acme/plugin@1.0.0".to_string()],
+        );
+        assert_eq!(ready, ["@acme/plugin@1.0.0"]);
+    }
+
+    #[test]
+    fn plugins_npm_cannot_install_are_left_out() {
+        let temp = TempDir::new("plugins-npm");
+        let cache_dir = temp.path();
+        install(cache_dir, "kept", "1.0.0");
+        // An older version in the cache doesn't count either.
+        install(cache_dir, "outdated", "1.0.0");
+
+        let ready = prepare(
+            cache_dir,
+            Err("no bundle"),
+            &no_npm(),
+            "",
+            &[
+                "kept@1.0.0".to_string(),
+                "outdated@2.0.0".to_string(),
+                "missing@1.0.0".to_string(),
+                "invalid@latest".to_string(),
+            ],
+        );
+        assert_eq!(ready, ["kept@1.0.0"]);
+    }
+
+    #[test]
+    fn bundled_plugins_are_unpacked_into_the_cache() {
+        let (name, version) = bundled_versions().into_iter().next().unwrap();
+        let temp = TempDir::new("plugins-bundle");
+        let archive = temp.path().join(BUNDLE_ARCHIVE);
+        {
+            let staging = temp.path().join("staging");
+            install(&staging, &name, &version);
+            let gz = flate2::write::GzEncoder::new(
+                std::fs::File::create(&archive).unwrap(),
+                flate2::Compression::fast(),
+            );
+            let mut tar = tar::Builder::new(gz);
+            tar.append_dir_all("node_modules", staging.join("node_modules"))
+                .unwrap();
+            tar.into_inner().unwrap().finish().unwrap();
+        }
+        let cache_dir = temp.path().join("cache");
+        let spec = format!("{name}@{version}");
+
+        let ready = prepare(
+            &cache_dir,
+            Ok(&archive),
+            &no_npm(),
+            "",
+            std::slice::from_ref(&spec),
+        );
+        assert_eq!(ready, [spec]);
+        assert_eq!(
+            installed_version(&cache_dir, &name).as_deref(),
+            Some(version.as_str())
+        );
+        assert_eq!(
+            read_cache_manifest(&cache_dir)["dependencies"][&name],
+            version.as_str()
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Port allocation for the OpenCode server, the MCP bridge, the MCP
+//! launcher's control endpoint and the managed Rojo server.
+//!
+//! The block for each server is configured in `AppConfig::ports` (see
+//! `config::PortConfig` for the defaults).
+//!
+//! Each port is probed independently. A probed port stays bound (held by a
+//! `PortReservation`) until the caller is about to hand it to the process
+//! that will listen on it, so two allocations can't race each other for
+//! the same port. If a whole block is taken, the allocator falls back to
+//! an OS-assigned ephemeral port.
+
+use std::net::TcpListener;
+
+use crate::config::PortConfig;
+use crate::opencode::LOOPBACK;
+
+// ── Types ───────────────────────────────────────────────────────────────
+
+/// Which server a port is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortKind {
+    OpenCode,
+    Bridge,
+    Control,
+    Rojo,
+}
+
+/// Start of each reserved block and the block size.
+#[derive(Debug, Clone, Copy)]
+pub struct PortRanges {
+    pub opencode_start: u16,
+    pub bridge_start: u16,
+    pub control_start: u16,
+    pub rojo_start: u16,
+    pub size: u16,
+}
+
+impl From<&PortConfig> for PortRanges {
+    fn from(cfg: &PortConfig) -> Self {
+        Self {
+            opencode_start: cfg.opencode_start,
+            bridge_start: cfg.bridge_start,
+            control_start: cfg.control_start,
+            rojo_start: cfg.rojo_start,
+            size: cfg.range_size,
+        }
+    }
+}
+
+impl PortRanges {
+    /// The ranges from the current `AppConfig`.
+    pub fn configured() -> Self {
+        Self::from(&crate::config::get().ports)
+    }
+
+    /// First port of the block reserved for `kind`.
+    pub fn start(&self, kind: PortKind) -> u16 {
+        match kind {
+            PortKind::OpenCode => self.opencode_start,
+            PortKind::Bridge => self.bridge_start,
+            PortKind::Control => self.control_start,
+            PortKind::Rojo => self.rojo_start,
+        }
+    }
+
+    /// All ports in all blocks, for stale-process cleanup.
+    pub fn all(&self) -> impl Iterator<Item = u16> + '_ {
+        [
+            PortKind::OpenCode,
+            PortKind::Bridge,
+            PortKind::Control,
+            PortKind::Rojo,
+        ]
+        .into_iter()
+        .flat_map(move |kind| {
+            let start = self.start(kind);
+            start..start.saturating_add(self.size)
+        })
+    }
+}
+
+/// Why a port could not be allocated.
+#[derive(Debug, Clone)]
+pub enum PortError {
+    /// Every port in the reserved block is taken and the OS refused to
+    /// hand out an ephemeral port as well.
+    Exhausted {
+        kind: PortKind,
+        start: u16,
+        end: u16,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for PortError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortError::Exhausted {
+                kind,
+                start,
+                end,
+                reason,
+            } => write!(
+                f,
+                "No free port for the {kind:?} server: {start}-{end} are in use and no ephemeral port is available ({reason})"
+            ),
+        }
+    }
+}
+
+/// A port that is bound by us until `release` is called.
+#[derive(Debug)]
+pub struct PortReservation {
+    pub kind: PortKind,
+    pub port: u16,
+    listener: TcpListener,
+}
+
+impl PortReservation {
+    /// Unbind the port so the child process can bind it. Call this as
+    /// late as possible — right before spawning.
+    pub fn release(self) -> u16 {
+        drop(self.listener);
+        log::debug!("Released {:?} port {}", self.kind, self.port);
+        self.port
+    }
+}
+
+/// Reservations for the three servers of the OpenCode stack. The Rojo
+/// port is reserved separately by `rojo::start`.
+#[derive(Debug)]
+pub struct PortSet {
+    pub opencode: PortReservation,
+    pub bridge: PortReservation,
+    pub control: PortReservation,
+}
+
+// ── Allocation ──────────────────────────────────────────────────────────
+
+/// `true` if something is listening on `port`. Used after a server failed
+/// to come up, to tell whether another process took its port.
+pub fn is_taken(port: u16) -> bool {
+    TcpListener::bind((LOOPBACK, port)).is_err()
+}
+
+/// Reserve one port for each of the three servers.
+pub fn allocate(ranges: &PortRanges) -> Result<PortSet, PortError> {
+    Ok(PortSet {
+        opencode: reserve(PortKind::OpenCode, ranges)?,
+        bridge: reserve(PortKind::Bridge, ranges)?,
+        control: reserve(PortKind::Control, ranges)?,
+    })
+}
+
+/// Reserve the first free port in the block for `kind`, or an ephemeral
+/// port if the whole block is taken. All servers bind to `LOOPBACK`
+/// (127.0.0.1), so we only need to probe that address.
+pub fn reserve(kind: PortKind, ranges: &PortRanges) -> Result<PortReservation, PortError> {
+    let start = ranges.start(kind);
+    let end = start.saturating_add(ranges.size.max(1) - 1);
+
+    for port in start..=end {
+        match TcpListener::bind((LOOPBACK, port)) {
+            Ok(listener) => {
+                return Ok(PortReservation {
+                    kind,
+                    port,
+                    listener,
+                })
+            }
+            Err(_) => log::debug!("Port {port} unavailable, skipping"),
+        }
+    }
+
+    log::warn!("All {kind:?} ports {start}-{end} are in use; falling back to an ephemeral port");
+    let listener = TcpListener::bind((LOOPBACK, 0)).map_err(|e| PortError::Exhausted {
+        kind,
+        start,
+        end,
+        reason: e.to_string(),
+    })?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| PortError::Exhausted {
+            kind,
+            start,
+            end,
+            reason: e.to_string(),
+        })?
+        .port();
+    Ok(PortReservation {
+        kind,
+        port,
+        listener,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Four blocks starting at the same port; `reserve` only looks at the
+    /// one for the kind it is asked for.
+    fn ranges(start: u16, size: u16) -> PortRanges {
+        PortRanges {
+            opencode_start: start,
+            bridge_start: start,
+            control_start: start,
+            rojo_start: start,
+            size,
+        }
+    }
+
+    /// Bind every port in `start..start + size` that is still free, so the
+    /// whole block is taken while the listeners live.
+    fn occupy(start: u16, size: u16) -> Vec<TcpListener> {
+        (start..start + size)
+            .filter_map(|port| TcpListener::bind((LOOPBACK, port)).ok())
+            .collect()
+    }
+
+    /// Start of a block that is likely free, picked by the OS.
+    fn free_start() -> u16 {
+        let port = TcpListener::bind((LOOPBACK, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        port.min(u16::MAX - 16)
+    }
+
+    #[test]
+    fn reservation_holds_the_port_until_released() {
+        let start = free_start();
+        let reservation = reserve(PortKind::Rojo, &ranges(start, 8)).unwrap();
+        assert!(is_taken(reservation.port));
+        let port = reservation.release();
+        assert!(!is_taken(port));
+    }
+
+    #[test]
+    fn reserve_skips_taken_ports() {
+        let start = free_start();
+        let _taken = occupy(start, 1);
+        let reservation = reserve(PortKind::OpenCode, &ranges(start, 8)).unwrap();
+        assert!(reservation.port > start && reservation.port < start + 8);
+    }
+
+    #[test]
+    fn exhausted_block_falls_back_to_an_ephemeral_port() {
+        let start = free_start();
+        let _taken = occupy(start, 4);
+        let reservation = reserve(PortKind::Bridge, &ranges(start, 4)).unwrap();
+        assert!(!(start..start + 4).contains(&reservation.port));
+        assert!(is_taken(reservation.port));
+    }
+
+    #[test]
+    fn allocate_hands_out_distinct_ports() {
+        let start = free_start();
+        let set = allocate(&ranges(start, 8)).unwrap();
+        let ports = [set.opencode.port, set.bridge.port, set.control.port];
+        assert_ne!(ports[0], ports[1]);
+        assert_ne!(ports[1], ports[2]);
+        assert_ne!(ports[0], ports[2]);
+    }
+}
@@ -0,0 +1,347 @@
+//! Registry of processes spawned on behalf of this BloxBot install.
+//!
+//! Every time BloxBot starts the OpenCode sidecar (and, indirectly, the MCP
+//! launcher and MCP server) or the Rojo server, it records the PID together
+//! with the process start time and command line in
+//! `~/BloxBot/.opencode/state/bloxbot-processes.json`.
+//!
+//! `opencode::cleanup_stale_processes` uses this file to decide what it may
+//! kill: a PID is only considered ours if it is recorded here *and* its
+//! current start time and command line still match what was recorded.
+//! That rules out PID reuse by an unrelated program. Anything else found on
+//! our ports is logged and left alone.
+//!
+//! Process inspection shells out to platform tools, like the rest of the
+//! cleanup code:
+//! - macOS/Linux: `ps -o lstart=` / `ps -o command=`
+//! - Windows: `Get-CimInstance Win32_Process` via PowerShell
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+// ── Types ───────────────────────────────────────────────────────────────
+
+/// What a recorded process is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessRole {
+    /// The `opencode serve` sidecar spawned directly by BloxBot.
+    Sidecar,
+    /// The Node.js MCP launcher (`launcher.js`) spawned by OpenCode.
+    Launcher,
+    /// The robloxstudio-mcp server spawned by the launcher.
+    McpServer,
+    /// The `rojo serve` process spawned by BloxBot (see `rojo`).
+    Rojo,
+}
+
+/// A process BloxBot spawned, identified by PID plus start time so a
+/// recycled PID is never mistaken for ours.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnedProcess {
+    pub role: ProcessRole,
+    pub pid: u32,
+    pub started_at: String,
+    pub command: String,
+}
+
+/// Start time and command line of a live process, as reported by the OS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub started_at: String,
+    pub command: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Registry {
+    /// The BloxBot app process that wrote this file.
+    owner_pid: u32,
+    owner_started_at: Option<String>,
+    processes: Vec<OwnedProcess>,
+}
+
+const REGISTRY_FILENAME: &str = "bloxbot-processes.json";
+
+/// Serialises read-modify-write cycles on the registry file.
+static LOCK: Mutex<()> = Mutex::new(());
+
+// ── File I/O ────────────────────────────────────────────────────────────
+
+fn registry_path() -> Result<PathBuf, String> {
+    let dir = crate::paths::workspace_root()?
+        .join(".opencode")
+        .join("state");
+    Ok(dir.join(REGISTRY_FILENAME))
+}
+
+fn load() -> Registry {
+    let Ok(path) = registry_path() else {
+        return Registry::default();
+    };
+    match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            log::warn!("Corrupt process registry, ignoring: {e}");
+            Registry::default()
+        }),
+        Err(_) => Registry::default(),
+    }
+}
+
+fn save(registry: &Registry) {
+    let path = match registry_path() {
+        Ok(p) => p,
+        Err(e) => {
+            log::error!("Cannot resolve process registry path: {e}");
+            return;
+        }
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match serde_json::to_string_pretty(registry) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                log::error!("Failed to write process registry: {e}");
+            }
+        }
+        Err(e) => log::error!("Failed to serialize process registry: {e}"),
+    }
+}
+
+// ── Public API ──────────────────────────────────────────────────────────
+
+/// Record `pid` as the current process for `role`, replacing any earlier
+/// entry for that role. A no-op if the same PID is already recorded.
+pub fn record(role: ProcessRole, pid: u32) {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut registry = load();
+
+    if registry
+        .processes
+        .iter()
+        .any(|p| p.role == role && p.pid == pid)
+    {
+        return;
+    }
+
+    let Some(info) = process_info(pid) else {
+        log::warn!("Cannot inspect {role:?} PID {pid}; not recording it");
+        return;
+    };
+
+    registry.owner_pid = std::process::id();
+    registry.owner_started_at = process_info(std::process::id()).map(|i| i.started_at);
+    registry.processes.retain(|p| p.role != role);
+    registry.processes.push(OwnedProcess {
+        role,
+        pid,
+        started_at: info.started_at,
+        command: info.command,
+    });
+    save(&registry);
+    log::debug!("Recorded {role:?} PID {pid}");
+}
+
+/// Result of checking the registry during stale-process cleanup.
+pub struct Ownership {
+    /// Recorded processes that are still alive and verifiably ours.
+    pub owned: Vec<OwnedProcess>,
+    /// Set when the processes belong to another BloxBot instance that is
+    /// still running. Nothing may be killed in that case.
+    pub live_owner: Option<u32>,
+}
+
+/// Check which recorded processes are still alive and provably ours.
+/// Dead or recycled PIDs are dropped from the registry as a side effect.
+pub fn verify_owned() -> Ownership {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut registry = load();
+
+    let own_pid = std::process::id();
+    if registry.owner_pid != 0 && registry.owner_pid != own_pid {
+        let owner_alive = match (&registry.owner_started_at, process_info(registry.owner_pid)) {
+            (Some(recorded), Some(info)) => *recorded == info.started_at,
+            _ => false,
+        };
+        if owner_alive {
+            return Ownership {
+                owned: Vec::new(),
+                live_owner: Some(registry.owner_pid),
+            };
+        }
+    }
+
+    let before = registry.processes.len();
+    registry.processes.retain(|p| match process_info(p.pid) {
+        Some(info) if info.started_at == p.started_at && info.command == p.command => true,
+        Some(info) => {
+            log::info!(
+                "PID {} was recorded as {:?} but is now a different process ({}); forgetting it",
+                p.pid,
+                p.role,
+                info.command
+            );
+            false
+        }
+        None => false,
+    });
+    if registry.processes.len() != before {
+        save(&registry);
+    }
+
+    Ownership {
+        owned: registry.processes.clone(),
+        live_owner: None,
+    }
+}
+
+/// Remove `pid` from the registry (after it has been terminated).
+pub fn forget(pid: u32) {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut registry = load();
+    let before = registry.processes.len();
+    registry.processes.retain(|p| p.pid != pid);
+    if registry.processes.len() != before {
+        save(&registry);
+    }
+}
+
+// ── Process inspection ──────────────────────────────────────────────────
+
+/// Query the start time and full command line of a live process.
+/// Returns `None` if the process does not exist or cannot be inspected.
+pub fn process_info(pid: u32) -> Option<ProcessInfo> {
+    #[cfg(unix)]
+    {
+        let ps = |field: &str| -> Option<String> {
+            let out = std::process::Command::new("ps")
+                .args(["-o", field, "-p", &pid.to_string()])
+                .output()
+                .ok()?;
+            if !out.status.success() {
+                return None;
+            }
+            let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            (!text.is_empty()).then_some(text)
+        };
+        Some(ProcessInfo {
+            started_at: ps("lstart=")?,
+            command: ps("command=")?,
+        })
+    }
+
+    #[cfg(windows)]
+    {
+        let script = format!(
+            "$p = Get-CimInstance Win32_Process -Filter 'ProcessId={pid}'; \
+             if ($p) {{ $p.CreationDate.ToString('o'); $p.CommandLine }}"
+        );
+        let out = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&out.stdout);
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+        let started_at = lines.next()?.to_string();
+        let command = lines.next().unwrap_or_default().to_string();
+        Some(ProcessInfo {
+            started_at,
+            command,
+        })
+    }
+}
+
+/// Returns `true` if a process with this PID currently exists.
+pub fn is_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .output()
+            .map(|out| out.status.success())
+            .unwrap_or(false)
+    }
+
+    #[cfg(windows)]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/NH", "/FO", "CSV"])
+            .output()
+            .map(|out| String::from_utf8_lossy(&out.stdout).contains(&format!("\"{pid}\"")))
+            .unwrap_or(false)
+    }
+}
+
+/// Politely ask a process and everything it spawned to exit.
+///
+/// On macOS/Linux this sends `SIGTERM` to `pid` and each of its
+/// descendants individually. Signalling the process group instead is not
+/// an option: the shell plugin spawns the sidecar (and OpenCode its MCP
+/// children) in *our* process group, so that would terminate BloxBot too.
+/// On Windows it runs `taskkill /T /PID` (without `/F`), which posts a
+/// close request to the whole tree.
+pub fn request_terminate(pid: u32) {
+    #[cfg(unix)]
+    {
+        for target in std::iter::once(pid).chain(descendants(pid)) {
+            let _ = std::process::Command::new("kill")
+                .args(["-TERM", &target.to_string()])
+                .output();
+        }
+    }
+
+    #[cfg(windows)]
+    let _ = std::process::Command::new("taskkill")
+        .args(["/T", "/PID", &pid.to_string()])
+        .output();
+}
+
+/// All transitive children of `pid`, from `ps -A -o pid=,ppid=`.
+#[cfg(unix)]
+fn descendants(pid: u32) -> Vec<u32> {
+    let Ok(out) = std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid="])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let table: Vec<(u32, u32)> = String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut cols = line.split_whitespace();
+            let child = cols.next()?.parse().ok()?;
+            let parent = cols.next()?.parse().ok()?;
+            Some((child, parent))
+        })
+        .collect();
+
+    let mut found = Vec::new();
+    let mut frontier = vec![pid];
+    while let Some(parent) = frontier.pop() {
+        for &(child, ppid) in &table {
+            if ppid == parent && !found.contains(&child) {
+                found.push(child);
+                frontier.push(child);
+            }
+        }
+    }
+    found
+}
+
+/// Forcefully terminate a process.
+///
+/// Uses `kill -9` on macOS/Linux and `taskkill /F /PID` on Windows.
+pub fn terminate(pid: u32) {
+    #[cfg(unix)]
+    let _ = std::process::Command::new("kill")
+        .args(["-9", &pid.to_string()])
+        .output();
+
+    #[cfg(windows)]
+    let _ = std::process::Command::new("taskkill")
+        .args(["/F", "/PID", &pid.to_string()])
+        .output();
+}
@@ -0,0 +1,465 @@
+//! Project registry.
+//!
+//! Each project is a directory OpenCode runs in, with its own session
+//! history and isolated `.opencode` data (XDG config, data, cache and
+//! state). The `default` project lives in `<root>/default`; the others are
+//! listed in `AppConfig::projects` and live under `<root>/projects/<name>`
+//! unless they were created with an explicit path. No project directory
+//! contains another, so an agent in one project cannot reach the others.
+//!
+//! The default project's OpenCode data stays in `<root>/.opencode`, where
+//! it was when the workspace root itself was the only project, so older
+//! installs keep their sessions; their files are moved into
+//! `<root>/default` once, at startup (see `migrate_legacy_default`). Provider logins (OpenCode's `auth.json`)
+//! are shared: every other project links the default project's file.
+//!
+//! Switching projects restarts the sidecar: its working directory and XDG
+//! environment both come from `paths::workspace_dir`, which follows
+//! `AppConfig::current_project`.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::config::{AppConfig, Project};
+use crate::opencode::SharedOpenCodeState;
+
+/// Name of the project that lives in the workspace root itself.
+pub const DEFAULT_PROJECT: &str = "default";
+
+/// Folder in the workspace root that holds managed projects.
+pub const PROJECTS_DIR: &str = "projects";
+
+/// OpenCode's data folder under a project's `.opencode`.
+const OPENCODE_DIR: &str = ".opencode";
+
+/// Left in `<root>/default` while legacy files are being moved into it, so
+/// an interrupted move resumes at the next startup.
+const MOVING_MARKER: &str = ".bloxbot-moving";
+
+/// Where OpenCode keeps provider logins, relative to `XDG_DATA_HOME`.
+const AUTH_FILE: [&str; 2] = ["opencode", "auth.json"];
+
+/// A project as returned to the frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectInfo {
+    pub name: String,
+    pub path: String,
+    pub current: bool,
+    pub is_default: bool,
+}
+
+// ── Directories ─────────────────────────────────────────────────────────
+
+fn managed_dir(root: &Path, name: &str) -> PathBuf {
+    root.join(PROJECTS_DIR).join(name)
+}
+
+fn project_dir(root: &Path, project: &Project) -> PathBuf {
+    project
+        .path
+        .clone()
+        .unwrap_or_else(|| managed_dir(root, &project.name))
+}
+
+fn default_dir(root: &Path) -> PathBuf {
+    root.join(DEFAULT_PROJECT)
+}
+
+/// The current project, `None` for the default one.
+fn current_project(cfg: &AppConfig) -> Option<&Project> {
+    cfg.current_project
+        .as_ref()
+        .and_then(|name| cfg.projects.iter().find(|p| &p.name == name))
+}
+
+/// Directory of the current project, created if missing.
+pub fn current_dir() -> Result<PathBuf, String> {
+    let root = crate::paths::workspace_root()?;
+    let cfg = crate::config::get();
+    let Some(project) = current_project(&cfg) else {
+        let dir = default_dir(&root);
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        }
+        return Ok(dir);
+    };
+    let dir = project_dir(&root, project);
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    Ok(dir)
+}
+
+/// The `.opencode` folder holding the current project's OpenCode data.
+pub fn current_opencode_home() -> Result<PathBuf, String> {
+    let root = crate::paths::workspace_root()?;
+    Ok(match current_project(&crate::config::get()) {
+        Some(project) => project_dir(&root, project).join(OPENCODE_DIR),
+        None => root.join(OPENCODE_DIR),
+    })
+}
+
+/// Before projects had their own directories, the default project was the
+/// workspace root itself. Called once at startup to move such a root's files
+/// into `<root>/default`.
+pub fn migrate_legacy_default() -> Result<(), String> {
+    move_legacy_default(&crate::paths::workspace_root()?)
+}
+
+/// Move the files of a legacy root into its default project, leaving the
+/// OpenCode data, the config override and the managed projects where they
+/// are. Only a root that
+/// has OpenCode data but no `default/` counts as legacy, since the root may
+/// be any folder the user picked. A move that failed part way is finished
+/// on the next call.
+fn move_legacy_default(root: &Path) -> Result<(), String> {
+    let dir = default_dir(root);
+    let marker = dir.join(MOVING_MARKER);
+    if dir.exists() {
+        if !marker.exists() {
+            return Ok(());
+        }
+        log::info!("Resuming the move of legacy files into the default project");
+    } else if root.join(OPENCODE_DIR).is_dir() {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        std::fs::write(&marker, "")
+            .map_err(|e| format!("Failed to write {}: {e}", marker.display()))?;
+    } else {
+        return Ok(());
+    }
+
+    let entries =
+        std::fs::read_dir(root).map_err(|e| format!("Failed to read {}: {e}", root.display()))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {e}", root.display()))?;
+        let name = entry.file_name();
+        if [
+            OPENCODE_DIR,
+            PROJECTS_DIR,
+            DEFAULT_PROJECT,
+            crate::opencode_config::OVERRIDE_FILENAME,
+        ]
+        .iter()
+        .any(|skip| name == *skip)
+        {
+            continue;
+        }
+        let to = dir.join(&name);
+        std::fs::rename(entry.path(), &to).map_err(|e| {
+            format!(
+                "Failed to move {} to {}: {e}",
+                entry.path().display(),
+                to.display()
+            )
+        })?;
+        log::info!("Moved {} into the default project", name.to_string_lossy());
+    }
+    std::fs::remove_file(&marker).map_err(|e| format!("Failed to remove {}: {e}", marker.display()))
+}
+
+// ── Shared logins ───────────────────────────────────────────────────────
+
+/// Make the `auth.json` under `xdg_data` the default project's, so a
+/// provider login made in any project applies to all of them. Logins the
+/// project already had on its own are merged into the shared file first.
+pub fn link_shared_auth(root: &Path, xdg_data: &Path) -> Result<(), String> {
+    let shared = AUTH_FILE
+        .iter()
+        .fold(root.join(OPENCODE_DIR).join("data"), |p, c| p.join(c));
+    let local = AUTH_FILE
+        .iter()
+        .fold(xdg_data.to_path_buf(), |p, c| p.join(c));
+    if local == shared {
+        return Ok(());
+    }
+    for dir in [shared.parent(), local.parent()].into_iter().flatten() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    if std::fs::read_link(&local).is_ok_and(|target| target == shared) {
+        return Ok(());
+    }
+
+    let mut logins = read_logins(&shared)?;
+    if local.exists() {
+        for (provider, login) in read_logins(&local)? {
+            logins.entry(provider).or_insert(login);
+        }
+    }
+    write_logins(&shared, &logins)?;
+    if std::fs::symlink_metadata(&local).is_ok() {
+        std::fs::remove_file(&local)
+            .map_err(|e| format!("Failed to remove {}: {e}", local.display()))?;
+    }
+    link(&shared, &local)
+}
+
+type Logins = serde_json::Map<String, serde_json::Value>;
+
+fn read_logins(path: &Path) -> Result<Logins, String> {
+    match std::fs::read(path) {
+        Ok(bytes) if !bytes.is_empty() => serde_json::from_slice(&bytes)
+            .map_err(|e| format!("{} is not valid JSON: {e}", path.display())),
+        _ => Ok(Logins::new()),
+    }
+}
+
+fn write_logins(path: &Path, logins: &Logins) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(logins)
+        .map_err(|e| format!("Failed to serialize logins: {e}"))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// OpenCode rewrites `auth.json` in place, so a link keeps pointing at the
+/// shared file. Windows needs extra privileges for symlinks; a hard link
+/// works as long as the project is on the same volume as the root.
+#[cfg(unix)]
+fn link(shared: &Path, local: &Path) -> Result<(), String> {
+    std::os::unix::fs::symlink(shared, local)
+        .map_err(|e| format!("Failed to link {}: {e}", local.display()))
+}
+
+#[cfg(windows)]
+fn link(shared: &Path, local: &Path) -> Result<(), String> {
+    std::fs::hard_link(shared, local)
+        .map_err(|e| format!("Failed to link {}: {e}", local.display()))
+}
+
+fn list(cfg: &AppConfig) -> Result<Vec<ProjectInfo>, String> {
+    let root = crate::paths::workspace_root()?;
+    let default = ProjectInfo {
+        name: DEFAULT_PROJECT.to_string(),
+        path: default_dir(&root).to_string_lossy().to_string(),
+        current: cfg.current_project.is_none(),
+        is_default: true,
+    };
+    Ok(std::iter::once(default)
+        .chain(cfg.projects.iter().map(|p| ProjectInfo {
+            name: p.name.clone(),
+            path: project_dir(&root, p).to_string_lossy().to_string(),
+            current: cfg.current_project.as_ref() == Some(&p.name),
+            is_default: false,
+        }))
+        .collect())
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn list_projects() -> Result<Vec<ProjectInfo>, String> {
+    list(&crate::config::get())
+}
+
+/// Register a new project and create its directory. `path` places it
+/// outside the workspace root; it may point at an existing folder.
+#[tauri::command]
+pub fn create_project(
+    app: AppHandle,
+    name: String,
+    path: Option<String>,
+) -> Result<Vec<ProjectInfo>, String> {
+    let project = Project {
+        name,
+        path: path.map(PathBuf::from),
+    };
+    let dir = project_dir(&crate::paths::workspace_root()?, &project);
+    // Keep every project out of the others' reach.
+    if let Some(other) = list(&crate::config::get())?.into_iter().find(|p| {
+        let other = Path::new(&p.path);
+        dir.starts_with(other) || other.starts_with(&dir)
+    }) {
+        return Err(format!(
+            "{} overlaps project \"{}\"; pick a separate folder",
+            dir.display(),
+            other.name
+        ));
+    }
+    let cfg = crate::config::update(&app, |cfg| {
+        cfg.projects.push(project);
+        Ok(())
+    })?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    log::info!("Created project at {}", dir.display());
+    list(&cfg)
+}
+
+/// Make `name` the current project and restart OpenCode in it.
+#[tauri::command]
+pub async fn switch_project(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+    name: String,
+) -> Result<Vec<ProjectInfo>, String> {
+    let cfg = crate::config::update(&app, |cfg| {
+        cfg.current_project = if name == DEFAULT_PROJECT {
+            None
+        } else if cfg.projects.iter().any(|p| p.name == name) {
+            Some(name.clone())
+        } else {
+            return Err(format!("No project named \"{name}\""));
+        };
+        Ok(())
+    })?;
+    log::info!("Switched to project \"{name}\"");
+    crate::opencode::restart(state.inner(), &app).await?;
+    list(&cfg)
+}
+
+/// Remove `name` from the registry. With `delete_files`, a project under
+/// the workspace root also has its directory deleted; projects with an
+/// explicit path are never deleted from disk.
+#[tauri::command]
+pub fn remove_project(
+    app: AppHandle,
+    name: String,
+    delete_files: bool,
+) -> Result<Vec<ProjectInfo>, String> {
+    let mut removed = None;
+    let cfg = crate::config::update(&app, |cfg| {
+        if cfg.current_project.as_ref() == Some(&name) {
+            return Err("Switch to another project before removing this one".to_string());
+        }
+        let index = cfg
+            .projects
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| format!("No project named \"{name}\""))?;
+        removed = Some(cfg.projects.remove(index));
+        Ok(())
+    })?;
+
+    if let Some(project) = removed.filter(|p| delete_files && p.path.is_none()) {
+        let dir = managed_dir(&crate::paths::workspace_root()?, &project.name);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to delete {}: {e}", dir.display()))?;
+            log::info!("Deleted project directory {}", dir.display());
+        }
+    }
+    list(&cfg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn auth_file(data: &Path) -> PathBuf {
+        AUTH_FILE.iter().fold(data.to_path_buf(), |p, c| p.join(c))
+    }
+
+    #[test]
+    fn legacy_default_files_move_out_of_the_root() {
+        let temp = TempDir::new("projects-legacy");
+        let root = temp.path();
+        std::fs::write(root.join("default.project.json"), "{}").unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join(OPENCODE_DIR).join("data")).unwrap();
+        std::fs::create_dir_all(root.join(PROJECTS_DIR).join("other")).unwrap();
+        let override_file = root.join(crate::opencode_config::OVERRIDE_FILENAME);
+        std::fs::write(&override_file, "{}").unwrap();
+
+        move_legacy_default(root).unwrap();
+
+        let dir = default_dir(root);
+        assert!(dir.join("default.project.json").is_file());
+        assert!(dir.join("src").is_dir());
+        assert!(!dir.join(MOVING_MARKER).exists());
+        assert!(root.join(OPENCODE_DIR).join("data").is_dir());
+        assert!(root.join(PROJECTS_DIR).join("other").is_dir());
+        assert!(override_file.is_file());
+        assert!(!root.join("src").exists());
+
+        // Files added to the root later are left alone.
+        std::fs::write(root.join("notes.txt"), "").unwrap();
+        move_legacy_default(root).unwrap();
+        assert!(root.join("notes.txt").is_file());
+    }
+
+    #[test]
+    fn other_folders_are_not_treated_as_legacy_roots() {
+        let temp = TempDir::new("projects-foreign");
+        let root = temp.path();
+        std::fs::write(root.join("taxes.pdf"), "").unwrap();
+        std::fs::create_dir_all(root.join("photos")).unwrap();
+
+        move_legacy_default(root).unwrap();
+
+        assert!(root.join("taxes.pdf").is_file());
+        assert!(root.join("photos").is_dir());
+        assert!(!default_dir(root).exists());
+    }
+
+    #[test]
+    fn interrupted_legacy_move_is_resumed() {
+        let temp = TempDir::new("projects-resume");
+        let root = temp.path();
+        std::fs::create_dir_all(root.join(OPENCODE_DIR)).unwrap();
+        std::fs::write(root.join("default.project.json"), "{}").unwrap();
+        // `src` cannot be moved while the default project has a
+        // non-empty directory of the same name.
+        std::fs::write(root.join("src"), "").unwrap();
+        let dir = default_dir(root);
+        std::fs::create_dir_all(dir.join("src").join("client")).unwrap();
+        std::fs::write(dir.join(MOVING_MARKER), "").unwrap();
+
+        assert!(move_legacy_default(root).is_err());
+        assert!(dir.join(MOVING_MARKER).exists());
+        assert!(root.join("src").is_file());
+
+        std::fs::remove_dir_all(dir.join("src")).unwrap();
+        move_legacy_default(root).unwrap();
+        assert!(dir.join("src").is_file());
+        assert!(dir.join("default.project.json").is_file());
+        assert!(!dir.join(MOVING_MARKER).exists());
+        assert!(!root.join("default.project.json").exists());
+    }
+
+    #[test]
+    fn project_logins_are_merged_and_shared() {
+        let temp = TempDir::new("projects-auth");
+        let root = temp.path();
+        let shared = auth_file(&root.join(OPENCODE_DIR).join("data"));
+        std::fs::create_dir_all(shared.parent().unwrap()).unwrap();
+        std::fs::write(&shared, r#"{"anthropic":{"type":"api","key":"a"}}"#).unwrap();
+
+        let data = root
+            .join(PROJECTS_DIR)
+            .join("game")
+            .join(OPENCODE_DIR)
+            .join("data");
+        let local = auth_file(&data);
+        std::fs::create_dir_all(local.parent().unwrap()).unwrap();
+        std::fs::write(
+            &local,
+            r#"{"anthropic":{"type":"api","key":"b"},"openai":{"type":"api","key":"c"}}"#,
+        )
+        .unwrap();
+
+        link_shared_auth(root, &data).unwrap();
+        let logins = read_logins(&local).unwrap();
+        assert_eq!(logins["anthropic"]["key"], "a");
+        assert_eq!(logins["openai"]["key"], "c");
+
+        // Logins made later in either place show up in both.
+        std::fs::write(&local, r#"{"google":{"type":"api","key":"d"}}"#).unwrap();
+        assert!(read_logins(&shared).unwrap().contains_key("google"));
+        // Linking again is a no-op.
+        link_shared_auth(root, &data).unwrap();
+        assert!(read_logins(&local).unwrap().contains_key("google"));
+    }
+}
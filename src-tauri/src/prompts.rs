@@ -0,0 +1,379 @@
+//! The `studio` agent's system prompt.
+//!
+//! The default prompt is a Markdown template compiled into the app from
+//! `prompts/studio.md`. A workspace can override it by placing its own
+//! template at `~/BloxBot/prompts/studio.md`; the `*_studio_prompt`
+//! commands manage that file from the UI.
+//!
+//! Templates may contain `{{variable}}` placeholders, filled in every time
+//! the OpenCode config is generated:
+//! - `project_name` — name of the workspace folder
+//! - `place_name` — name of the place open in the connected Studio; before
+//!   a plugin has connected, `name` from the workspace's Rojo project file,
+//!   then the project name
+//! - `framework` — frameworks detected from the Rojo/Wally manifests
+//!
+//! The default template is versioned. An override records the version it
+//! was based on in a marker comment on its first line, so the UI can point
+//! out overrides that predate a newer default.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::opencode::SharedOpenCodeState;
+
+const DEFAULT_STUDIO_PROMPT: &str = include_str!("../prompts/studio.md");
+
+/// Bump whenever `prompts/studio.md` changes meaningfully.
+pub const DEFAULT_VERSION: u32 = 1;
+
+const BASE_MARKER_PREFIX: &str = "<!-- bloxbot-prompt-base: ";
+const BASE_MARKER_SUFFIX: &str = " -->";
+
+/// Placeholders a template may use.
+pub const VARIABLES: &[&str] = &["project_name", "place_name", "framework"];
+
+// ── Types ───────────────────────────────────────────────────────────────
+
+/// The prompt as returned to the frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StudioPrompt {
+    /// The unrendered template (without the version marker).
+    pub content: String,
+    /// `true` if a workspace override is in effect.
+    pub is_override: bool,
+    pub default_version: u32,
+    /// Default version the override was based on, if it recorded one.
+    pub override_base_version: Option<u32>,
+    /// `true` if the override was based on an older default.
+    pub outdated: bool,
+    pub variables: Vec<&'static str>,
+}
+
+// ── Template files ──────────────────────────────────────────────────────
+
+fn override_path() -> Result<PathBuf, String> {
+    Ok(crate::paths::workspace_dir()?
+        .join("prompts")
+        .join("studio.md"))
+}
+
+/// Split the version marker off an override. Returns the recorded base
+/// version and the template body.
+fn split_marker(text: &str) -> (Option<u32>, &str) {
+    let Some(rest) = text.strip_prefix(BASE_MARKER_PREFIX) else {
+        return (None, text);
+    };
+    let Some((line, body)) = rest.split_once('\n') else {
+        return (None, text);
+    };
+    match line
+        .trim_end()
+        .strip_suffix(BASE_MARKER_SUFFIX)
+        .and_then(|v| v.trim().parse().ok())
+    {
+        Some(version) => (Some(version), body),
+        None => (None, text),
+    }
+}
+
+/// Read the workspace override, if there is one.
+fn read_override() -> Option<(Option<u32>, String)> {
+    let path = override_path().ok()?;
+    let text = std::fs::read_to_string(&path).ok()?;
+    let (version, body) = split_marker(&text);
+    Some((version, body.to_string()))
+}
+
+fn current() -> StudioPrompt {
+    let (content, is_override, override_base_version) = match read_override() {
+        Some((version, body)) => (body, true, version),
+        None => (DEFAULT_STUDIO_PROMPT.to_string(), false, None),
+    };
+    StudioPrompt {
+        content,
+        is_override,
+        default_version: DEFAULT_VERSION,
+        override_base_version,
+        outdated: is_override && override_base_version.is_none_or(|v| v < DEFAULT_VERSION),
+        variables: VARIABLES.to_vec(),
+    }
+}
+
+// ── Rendering ───────────────────────────────────────────────────────────
+
+/// The studio prompt with all variables substituted, ready to go into
+/// `opencode.json`. `studio_place` is the name of the place open in the
+/// connected Studio, if known.
+pub fn render_studio_prompt(studio_place: Option<&str>) -> String {
+    let prompt = current();
+    if prompt.outdated {
+        log::warn!(
+            "Studio prompt override is based on an older default (v{}); current default is v{DEFAULT_VERSION}",
+            prompt
+                .override_base_version
+                .map_or_else(|| "?".to_string(), |v| v.to_string())
+        );
+    }
+    let vars = match crate::paths::workspace_dir() {
+        Ok(workspace) => variables(&workspace, studio_place),
+        Err(e) => {
+            log::warn!("Cannot resolve workspace for prompt variables: {e}");
+            Vec::new()
+        }
+    };
+    render(prompt.content.trim_end(), &vars)
+}
+
+/// Replace `{{name}}` placeholders. Unknown names are left as they are.
+fn render(template: &str, vars: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        out.push_str(&rest[..start]);
+        match vars.iter().find(|(k, _)| *k == name) {
+            Some((_, value)) => out.push_str(value),
+            None => {
+                log::warn!("Unknown studio prompt variable {{{{{name}}}}}");
+                out.push_str(&rest[start..start + 4 + len]);
+            }
+        }
+        rest = &rest[start + 4 + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Current values of all `VARIABLES` for `workspace`.
+fn variables(workspace: &Path, studio_place: Option<&str>) -> Vec<(&'static str, String)> {
+    let project_name = workspace
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "BloxBot".to_string());
+    let place_name = studio_place
+        .map(str::to_string)
+        .or_else(|| rojo_project_name(workspace))
+        .unwrap_or_else(|| project_name.clone());
+    let frameworks = detect_frameworks(workspace);
+    let framework = if frameworks.is_empty() {
+        "none detected".to_string()
+    } else {
+        frameworks.join(", ")
+    };
+    vec![
+        ("project_name", project_name),
+        ("place_name", place_name),
+        ("framework", framework),
+    ]
+}
+
+fn rojo_project_name(workspace: &Path) -> Option<String> {
+    let bytes = std::fs::read(workspace.join("default.project.json")).ok()?;
+    let json: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    json.get("name")?.as_str().map(str::to_string)
+}
+
+/// Dependency names (lowercase substrings of a `wally.toml` line) and the
+/// framework they indicate.
+const WALLY_FRAMEWORKS: &[(&str, &str)] = &[
+    ("sleitnick/knit", "Knit"),
+    ("elttob/fusion", "Fusion"),
+    ("roblox/roact", "Roact"),
+    ("jsdotlua/react", "React-lua"),
+    ("roblox/rodux", "Rodux"),
+    ("madstudioroblox/profileservice", "ProfileService"),
+    ("lm-loleris/profilestore", "ProfileStore"),
+    ("quenty/", "Nevermore"),
+    ("ffrostflame/bridgenet2", "BridgeNet2"),
+    ("red-blox/red", "Red"),
+];
+
+/// Best-effort detection from the Rojo and Wally manifests in the
+/// workspace root. The agent still scans the place itself; this only gives
+/// it a head start.
+fn detect_frameworks(workspace: &Path) -> Vec<&'static str> {
+    let mut found = Vec::new();
+    if workspace.join("default.project.json").exists() {
+        found.push("Rojo");
+    }
+    if let Ok(manifest) = std::fs::read_to_string(workspace.join("wally.toml")) {
+        let manifest = manifest.to_lowercase();
+        for (needle, name) in WALLY_FRAMEWORKS {
+            if manifest.contains(needle) && !found.contains(name) {
+                found.push(name);
+            }
+        }
+    }
+    found
+}
+
+// ── Apply ───────────────────────────────────────────────────────────────
+
+/// Push a prompt change to the running server.
+async fn apply(state: &SharedOpenCodeState, app: &AppHandle) {
+    match crate::opencode::reconfigure(state, app).await {
+        Ok(outcome) => log::info!("Studio prompt applied ({outcome:?})"),
+        Err(e) => log::warn!("Studio prompt saved but not applied yet: {e}"),
+    }
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+/// Return the studio prompt template currently in effect.
+#[tauri::command]
+pub fn get_studio_prompt() -> StudioPrompt {
+    current()
+}
+
+/// Save `content` as the workspace override and apply it.
+#[tauri::command]
+pub async fn set_studio_prompt(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+    content: String,
+) -> Result<StudioPrompt, String> {
+    let path = override_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    let (_, body) = split_marker(&content);
+    let text = format!("{BASE_MARKER_PREFIX}{DEFAULT_VERSION}{BASE_MARKER_SUFFIX}\n{body}");
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    log::info!("Saved studio prompt override to {}", path.display());
+
+    apply(state.inner(), &app).await;
+    Ok(current())
+}
+
+/// Unified diff from the default template to the override. Empty if no
+/// override is in effect.
+#[tauri::command]
+pub fn diff_studio_prompt() -> String {
+    let Some((_, body)) = read_override() else {
+        return String::new();
+    };
+    similar::TextDiff::from_lines(DEFAULT_STUDIO_PROMPT, body.as_str())
+        .unified_diff()
+        .header("default", "override")
+        .to_string()
+}
+
+/// Delete the workspace override and go back to the default template.
+#[tauri::command]
+pub async fn reset_studio_prompt(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+) -> Result<StudioPrompt, String> {
+    let path = override_path()?;
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+        log::info!("Removed studio prompt override {}", path.display());
+        apply(state.inner(), &app).await;
+    }
+    Ok(current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn split_marker_reads_the_base_version() {
+        let text = "<!-- bloxbot-prompt-base: 3 -->\nYou are BloxBot.";
+        assert_eq!(split_marker(text), (Some(3), "You are BloxBot."));
+        assert_eq!(
+            split_marker("<!-- bloxbot-prompt-base: 3 -->\r\nBody"),
+            (Some(3), "Body")
+        );
+    }
+
+    #[test]
+    fn split_marker_keeps_unmarked_and_malformed_text() {
+        for text in [
+            "You are BloxBot.",
+            "<!-- bloxbot-prompt-base: three -->\nBody",
+            "<!-- bloxbot-prompt-base: 3\nBody",
+            "<!-- bloxbot-prompt-base: 3 -->",
+            "Body\n<!-- bloxbot-prompt-base: 3 -->\n",
+        ] {
+            assert_eq!(split_marker(text), (None, text), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn render_substitutes_known_variables_only() {
+        let vars = [
+            ("place_name", "Crossroads".to_string()),
+            ("framework", "Knit".to_string()),
+        ];
+        assert_eq!(
+            render("Place {{place_name}} uses {{ framework }}.", &vars),
+            "Place Crossroads uses Knit."
+        );
+        assert_eq!(
+            render("Keep {{unknown}} and {{place_name}}", &vars),
+            "Keep {{unknown}} and Crossroads"
+        );
+        assert_eq!(
+            render("Unclosed {{place_name", &vars),
+            "Unclosed {{place_name"
+        );
+        assert_eq!(render("No variables", &vars), "No variables");
+    }
+
+    #[test]
+    fn detect_frameworks_reads_rojo_and_wally_manifests() {
+        let temp = TempDir::new("prompts-frameworks");
+        let workspace = temp.path();
+        assert!(detect_frameworks(workspace).is_empty());
+
+        std::fs::write(workspace.join("default.project.json"), "{}").unwrap();
+        std::fs::write(
+            workspace.join("wally.toml"),
+            "[dependencies]\n\
+             Knit = \"Sleitnick/Knit@^1.7\"\n\
+             Fusion = \"elttob/fusion@0.3\"\n\
+             Signal = \"sleitnick/signal@2\"\n\
+             Maid = \"quenty/maid@3\"\n\
+             Binder = \"quenty/binder@8\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect_frameworks(workspace),
+            ["Rojo", "Knit", "Fusion", "Nevermore"]
+        );
+    }
+
+    #[test]
+    fn place_name_prefers_studio_then_rojo_then_project() {
+        let temp = TempDir::new("prompts-variables");
+        let workspace = temp.path().join("obby");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let place_name = |studio_place| {
+            variables(&workspace, studio_place)
+                .into_iter()
+                .find(|(name, _)| *name == "place_name")
+                .unwrap()
+                .1
+        };
+
+        assert_eq!(place_name(None), "obby");
+        std::fs::write(
+            workspace.join("default.project.json"),
+            r#"{"name":"Obby Game"}"#,
+        )
+        .unwrap();
+        assert_eq!(place_name(None), "Obby Game");
+        assert_eq!(place_name(Some("Obby (live)")), "Obby (live)");
+    }
+}
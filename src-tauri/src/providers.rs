@@ -0,0 +1,170 @@
+//! Local model providers.
+//!
+//! `AppConfig::local_providers` lists OpenAI-compatible servers such as
+//! Ollama or LM Studio. They are emitted into the `provider` section of the
+//! generated `opencode.json` using OpenCode's bundled
+//! `@ai-sdk/openai-compatible` adapter, so no API key or npm download is
+//! needed. `probe_local_provider` asks a server which models it serves,
+//! which lets the UI validate a provider before saving it.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::config::LocalProvider;
+use crate::opencode_config::{ModelEntry, ModelLimit, ProviderEntry, ProviderOptions};
+
+/// AI SDK package OpenCode uses for OpenAI-compatible endpoints.
+const OPENAI_COMPATIBLE_NPM: &str = "@ai-sdk/openai-compatible";
+
+/// Output limit used when a model sets a context size but no output limit.
+const DEFAULT_MAX_OUTPUT: u32 = 4096;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ── OpenCode format ─────────────────────────────────────────────────────
+
+fn to_opencode(provider: &LocalProvider) -> ProviderEntry {
+    let models = provider
+        .models
+        .iter()
+        .map(|model| {
+            let entry = ModelEntry {
+                name: Some(model.name.clone().unwrap_or_else(|| model.id.clone())),
+                limit: model.context_size.map(|context| ModelLimit {
+                    context,
+                    output: model
+                        .max_output
+                        .unwrap_or_else(|| DEFAULT_MAX_OUTPUT.min(context)),
+                }),
+            };
+            (model.id.clone(), entry)
+        })
+        .collect();
+
+    ProviderEntry {
+        npm: Some(OPENAI_COMPATIBLE_NPM.to_string()),
+        name: Some(provider.name.clone().unwrap_or_else(|| provider.id.clone())),
+        options: ProviderOptions {
+            base_url: Some(provider.base_url.clone()),
+            api_key: provider.api_key.clone(),
+        },
+        models,
+    }
+}
+
+/// OpenCode's `provider` map for `providers`.
+pub fn provider_config(providers: &[LocalProvider]) -> BTreeMap<String, ProviderEntry> {
+    providers
+        .iter()
+        .map(|p| (p.id.clone(), to_opencode(p)))
+        .collect()
+}
+
+// ── Probe ───────────────────────────────────────────────────────────────
+
+/// IDs of the models served at `base_url`, from the OpenAI-compatible
+/// `GET {base_url}/models` endpoint.
+pub async fn list_models(base_url: &str, api_key: Option<&str>) -> Result<Vec<String>, String> {
+    let url = format!("{}/models", base_url.trim_end_matches('/'));
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+    let mut request = client.get(&url);
+    if let Some(key) = api_key.filter(|k| !k.is_empty()) {
+        request = request.bearer_auth(key);
+    }
+
+    let resp = request
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach {url}: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!("{url} returned HTTP {}", resp.status()));
+    }
+    let body: Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("{url} did not return JSON: {e}"))?;
+    let data = body
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("{url} is not an OpenAI-compatible model list"))?;
+    Ok(data
+        .iter()
+        .filter_map(|m| m.get("id").and_then(Value::as_str).map(str::to_string))
+        .collect())
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+/// Check that an OpenAI-compatible server is reachable and list the
+/// models it serves.
+#[tauri::command]
+pub async fn probe_local_provider(
+    base_url: String,
+    api_key: Option<String>,
+) -> Result<Vec<String>, String> {
+    let models = list_models(&base_url, api_key.as_deref()).await?;
+    log::info!("{base_url} serves {} model(s)", models.len());
+    Ok(models)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one HTTP response on a local port and return the base URL.
+    async fn stub_server(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            assert!(request.starts_with(b"GET /v1/models "));
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{addr}/v1")
+    }
+
+    #[tokio::test]
+    async fn list_models_returns_model_ids() {
+        let base_url = stub_server(
+            "200 OK",
+            r#"{"object":"list","data":[{"id":"llama3.1:8b"},{"id":"qwen2.5-coder"}]}"#,
+        )
+        .await;
+        let models = list_models(&base_url, None).await.unwrap();
+        assert_eq!(models, ["llama3.1:8b", "qwen2.5-coder"]);
+    }
+
+    #[tokio::test]
+    async fn list_models_fails_on_http_error() {
+        let base_url = stub_server("500 Internal Server Error", "{}").await;
+        let err = list_models(&base_url, None).await.unwrap_err();
+        assert!(err.contains("HTTP 500"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn list_models_fails_without_data() {
+        let base_url = stub_server("200 OK", r#"{"models":[]}"#).await;
+        let err = list_models(&base_url, None).await.unwrap_err();
+        assert!(err.contains("not an OpenAI-compatible model list"), "{err}");
+    }
+}
@@ -0,0 +1,585 @@
+//! Managed Rojo server.
+//!
+//! When the workspace is a Rojo project (it has a `default.project.json`),
+//! BloxBot runs `rojo serve` next to the OpenCode sidecar, so files the
+//! agent edits on disk sync into Studio through the Rojo plugin. The server
+//! binds a port from the `rojoStart` block, its output goes to the app log,
+//! and status changes are emitted as `rojo-status-changed`.
+//!
+//! Rojo follows the sidecar's lifecycle: it is started once OpenCode is
+//! running, restarted after a crash under the same `RestartPolicy`, and
+//! shut down by `opencode::stop_all`. Its state lives in
+//! `OpenCodeState::rojo` for that reason.
+//!
+//! The executable is `AppConfig::rojo.path` if set, otherwise `rojo` from
+//! `PATH` or the Rokit, Aftman and Foreman tool directories. Anything that
+//! accepts `serve <project> --address <ip> --port <port>` and listens on
+//! that port will do, so a stub script can stand in for it.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+use crate::opencode::{SharedOpenCodeState, LOOPBACK};
+use crate::ports::{PortKind, PortRanges};
+use crate::process_registry::ProcessRole;
+
+/// Project file that marks a workspace as a Rojo project.
+pub const PROJECT_FILE: &str = "default.project.json";
+
+#[cfg(unix)]
+const ROJO_BIN: &str = "rojo";
+#[cfg(windows)]
+const ROJO_BIN: &str = "rojo.exe";
+
+/// Toolchain manager directories (relative to the home directory) searched
+/// after `PATH`. GUI apps on macOS don't see the shell's `PATH`, so this is
+/// usually where Rojo is found there.
+const TOOL_DIRS: &[&str] = &[".rokit/bin", ".aftman/bin", ".foreman/bin"];
+
+/// How long a freshly spawned server may take to accept connections.
+const READY_TIMEOUT: Duration = Duration::from_secs(15);
+
+// ── State ───────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum RojoStatus {
+    /// Disabled in the config, or the workspace is not a Rojo project.
+    Inactive,
+    Starting,
+    Running,
+    /// Rojo crashed and the supervisor is waiting to restart it.
+    Restarting,
+    Stopped,
+    Error(String),
+}
+
+pub struct RojoState {
+    pub status: RojoStatus,
+    pub port: u16,
+    /// Project file being served.
+    pub project: Option<PathBuf>,
+    pub(crate) child: Option<CommandChild>,
+    /// Bumped on every spawn and intentional stop, like
+    /// `OpenCodeState::child_generation`.
+    pub(crate) generation: u64,
+    pub(crate) running_since: Option<Instant>,
+    pub restart_count: u32,
+}
+
+impl Default for RojoState {
+    fn default() -> Self {
+        Self {
+            status: RojoStatus::Inactive,
+            port: 0,
+            project: None,
+            child: None,
+            generation: 0,
+            running_since: None,
+            restart_count: 0,
+        }
+    }
+}
+
+impl RojoState {
+    pub fn pid(&self) -> Option<u32> {
+        self.child.as_ref().map(CommandChild::pid)
+    }
+
+    /// Take the child and bump the generation so its exit is not treated
+    /// as a crash.
+    pub(crate) fn detach(&mut self) -> Option<CommandChild> {
+        self.generation += 1;
+        self.running_since = None;
+        self.port = 0;
+        self.child.take()
+    }
+}
+
+/// Payload emitted with the `rojo-status-changed` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RojoStatusPayload {
+    pub status: RojoStatus,
+    pub port: u16,
+    pub project: Option<String>,
+    pub restart_count: u32,
+    pub max_restarts: u32,
+}
+
+fn payload(s: &RojoState) -> RojoStatusPayload {
+    RojoStatusPayload {
+        status: s.status.clone(),
+        port: s.port,
+        project: s.project.as_ref().map(|p| p.to_string_lossy().to_string()),
+        restart_count: s.restart_count,
+        max_restarts: crate::config::get().restart_policy.max_attempts,
+    }
+}
+
+pub fn emit_status<R: Runtime>(app: &AppHandle<R>, s: &RojoState) {
+    let _ = app.emit("rojo-status-changed", payload(s));
+}
+
+// ── Discovery ───────────────────────────────────────────────────────────
+
+/// The project file in `workspace`, if it is a Rojo project.
+pub fn project_file(workspace: &Path) -> Option<PathBuf> {
+    Some(workspace.join(PROJECT_FILE)).filter(|p| p.is_file())
+}
+
+fn find_binary() -> Result<PathBuf, String> {
+    if let Some(path) = crate::config::get().rojo.path {
+        return if path.is_file() {
+            Ok(path)
+        } else {
+            Err(format!("Rojo not found at {}", path.display()))
+        };
+    }
+    let path_dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|p| std::env::split_paths(&p).collect())
+        .unwrap_or_default();
+    let tool_dirs: Vec<PathBuf> = dirs::home_dir()
+        .map(|home| TOOL_DIRS.iter().map(|d| home.join(d)).collect())
+        .unwrap_or_default();
+    path_dirs
+        .into_iter()
+        .chain(tool_dirs)
+        .map(|dir| dir.join(ROJO_BIN))
+        .find(|p| p.is_file())
+        .ok_or_else(|| {
+            "Rojo is not installed; install it with Rokit or Aftman, or set rojo.path".to_string()
+        })
+}
+
+// ── Lifecycle ───────────────────────────────────────────────────────────
+
+/// Start `rojo serve` if the current workspace is a Rojo project. A no-op
+/// if Rojo is already running or starting. Called once OpenCode is up.
+pub async fn start<R: Runtime>(
+    state: SharedOpenCodeState,
+    app: AppHandle<R>,
+) -> Result<(), String> {
+    let enabled = crate::config::get().rojo.enabled;
+    let workspace = crate::paths::workspace_dir()?;
+    let project = {
+        let mut s = state.lock().await;
+        if s.shutting_down {
+            return Err("BloxBot is shutting down".to_string());
+        }
+        if s.rojo.child.is_some() || s.rojo.status == RojoStatus::Starting {
+            return Ok(());
+        }
+        let Some(project) = project_file(&workspace).filter(|_| enabled) else {
+            s.rojo.status = RojoStatus::Inactive;
+            s.rojo.project = None;
+            emit_status(&app, &s.rojo);
+            return Ok(());
+        };
+        s.rojo.status = RojoStatus::Starting;
+        s.rojo.project = Some(project.clone());
+        emit_status(&app, &s.rojo);
+        project
+    };
+
+    let result = do_start(&state, &app, &workspace, &project).await;
+    let mut s = state.lock().await;
+    match result {
+        Ok(generation) if s.rojo.generation == generation => {
+            log::info!("Rojo listening on port {}", s.rojo.port);
+            s.rojo.status = RojoStatus::Running;
+            s.rojo.running_since = Some(Instant::now());
+            emit_status(&app, &s.rojo);
+            Ok(())
+        }
+        Ok(_) => Ok(()),
+        Err(e) => {
+            log::error!("Rojo failed to start: {e}");
+            // Leave the status alone if Rojo was stopped on purpose or the
+            // exit handler already reported why.
+            if matches!(s.rojo.status, RojoStatus::Starting) {
+                if let Some(child) = s.rojo.detach() {
+                    crate::process_registry::forget(child.pid());
+                    let _ = child.kill();
+                }
+                s.rojo.status = RojoStatus::Error(e.clone());
+                emit_status(&app, &s.rojo);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Attempts when Rojo's port is taken between its release and Rojo binding
+/// it.
+const PORT_ATTEMPTS: u32 = 3;
+
+/// Spawn the server and wait until it accepts connections, with a new port
+/// if another process took the last one first. Returns the generation of
+/// the new child.
+async fn do_start<R: Runtime>(
+    state: &SharedOpenCodeState,
+    app: &AppHandle<R>,
+    workspace: &Path,
+    project: &Path,
+) -> Result<u64, String> {
+    let binary = find_binary()?;
+    let mut attempt = 1;
+    loop {
+        let reserved = crate::ports::reserve(PortKind::Rojo, &PortRanges::configured())
+            .map_err(|e| e.to_string())?;
+        let port = reserved.release();
+        let err = match spawn_and_wait(state, app, workspace, project, &binary, port).await {
+            Ok(generation) => return Ok(generation),
+            Err(e) => e,
+        };
+        if attempt == PORT_ATTEMPTS || !crate::ports::is_taken(port) {
+            return Err(err);
+        }
+        {
+            // Only retry an exit during startup, not a deliberate stop.
+            let mut s = state.lock().await;
+            if !matches!(s.rojo.status, RojoStatus::Error(_)) {
+                return Err(err);
+            }
+            s.rojo.status = RojoStatus::Starting;
+            emit_status(app, &s.rojo);
+        }
+        log::warn!(
+            "{err}; port {port} was taken by another process, retrying (attempt {attempt} of {PORT_ATTEMPTS})"
+        );
+        attempt += 1;
+    }
+}
+
+async fn spawn_and_wait<R: Runtime>(
+    state: &SharedOpenCodeState,
+    app: &AppHandle<R>,
+    workspace: &Path,
+    project: &Path,
+    binary: &Path,
+    port: u16,
+) -> Result<u64, String> {
+    let (rx, child) = app
+        .shell()
+        .command(binary)
+        .args([
+            "serve",
+            &project.to_string_lossy(),
+            "--address",
+            LOOPBACK,
+            "--port",
+            &port.to_string(),
+        ])
+        .current_dir(workspace)
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {e}", binary.display()))?;
+    log::info!(
+        "Started {} for {} (PID {})",
+        binary.display(),
+        project.display(),
+        child.pid()
+    );
+    crate::process_registry::record(ProcessRole::Rojo, child.pid());
+
+    let generation = {
+        let mut s = state.lock().await;
+        s.rojo.child = Some(child);
+        s.rojo.generation += 1;
+        s.rojo.port = port;
+        s.rojo.generation
+    };
+    spawn_event_handler(rx, Arc::clone(state), app.clone(), generation);
+
+    let deadline = Instant::now() + READY_TIMEOUT;
+    loop {
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        {
+            let s = state.lock().await;
+            if s.rojo.generation != generation || s.rojo.child.is_none() {
+                return Err("Rojo exited before it started listening".to_string());
+            }
+        }
+        if tokio::net::TcpStream::connect((LOOPBACK, port))
+            .await
+            .is_ok()
+        {
+            return Ok(generation);
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Rojo did not start listening on port {port} within {}s",
+                READY_TIMEOUT.as_secs()
+            ));
+        }
+    }
+}
+
+/// Kill Rojo right away. Used before the sidecar (re)starts, since stale
+/// process cleanup would otherwise kill it behind our back.
+pub async fn stop<R: Runtime>(state: &SharedOpenCodeState, app: &AppHandle<R>) {
+    let mut s = state.lock().await;
+    if let Some(child) = s.rojo.detach() {
+        log::info!("Stopping Rojo (PID {})", child.pid());
+        crate::process_registry::forget(child.pid());
+        let _ = child.kill();
+    }
+    if s.rojo.status != RojoStatus::Inactive {
+        s.rojo.status = RojoStatus::Stopped;
+        emit_status(app, &s.rojo);
+    }
+}
+
+// ── Output and supervision ──────────────────────────────────────────────
+
+/// Forward Rojo's output to the log and handle its exit. Runs on its own
+/// thread for the same reason as the sidecar's event handler.
+fn spawn_event_handler<R: Runtime>(
+    rx: tauri::async_runtime::Receiver<CommandEvent>,
+    state: SharedOpenCodeState,
+    app: AppHandle<R>,
+    generation: u64,
+) {
+    std::thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(e) => {
+                log::error!("Failed to build tokio runtime for Rojo event handler: {e}");
+                return;
+            }
+        };
+        rt.block_on(process_events(rx, &state, &app, generation));
+    });
+}
+
+async fn process_events<R: Runtime>(
+    mut rx: tauri::async_runtime::Receiver<CommandEvent>,
+    state: &SharedOpenCodeState,
+    app: &AppHandle<R>,
+    generation: u64,
+) {
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line) => {
+                let text = String::from_utf8_lossy(&line);
+                let trimmed = text.trim_end();
+                if !trimmed.is_empty() {
+                    log::info!(target: "rojo::stdout", "{trimmed}");
+                }
+            }
+            CommandEvent::Stderr(line) => {
+                let text = String::from_utf8_lossy(&line);
+                let trimmed = text.trim_end();
+                if !trimmed.is_empty() {
+                    log::warn!(target: "rojo::stderr", "{trimmed}");
+                }
+            }
+            CommandEvent::Terminated(payload) => {
+                handle_exit(state, app, &payload, generation).await;
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A crash of a running server is handed to the supervisor; an exit during
+/// startup becomes an `Error` status.
+async fn handle_exit<R: Runtime>(
+    state: &SharedOpenCodeState,
+    app: &AppHandle<R>,
+    payload: &tauri_plugin_shell::process::TerminatedPayload,
+    generation: u64,
+) {
+    let mut s = state.lock().await;
+    if s.rojo.generation != generation {
+        log::debug!("Ignoring exit of superseded Rojo process (generation {generation})");
+        return;
+    }
+    if let Some(pid) = s.rojo.pid() {
+        crate::process_registry::forget(pid);
+    }
+    s.rojo.child = None;
+    s.rojo.port = 0;
+    let msg = format!(
+        "Rojo exited with code {:?} (signal {:?})",
+        payload.code, payload.signal
+    );
+    log::warn!("{msg}");
+
+    if s.rojo.status == RojoStatus::Running {
+        let policy = crate::config::get().restart_policy;
+        let stable = s
+            .rojo
+            .running_since
+            .is_some_and(|since| since.elapsed() >= policy.reset_window());
+        if stable {
+            s.rojo.restart_count = 0;
+        }
+        s.rojo.running_since = None;
+        drop(s);
+        spawn_supervisor(Arc::clone(state), app.clone(), msg);
+        return;
+    }
+
+    s.rojo.running_since = None;
+    s.rojo.status = RojoStatus::Error(msg);
+    emit_status(app, &s.rojo);
+}
+
+/// Restart Rojo after a crash with the sidecar's backoff, giving up after
+/// `max_attempts`. Backs off if anything else changes the status meanwhile.
+fn spawn_supervisor<R: Runtime>(state: SharedOpenCodeState, app: AppHandle<R>, user_msg: String) {
+    tauri::async_runtime::spawn(async move {
+        let policy = crate::config::get().restart_policy;
+        let mut user_msg = user_msg;
+
+        loop {
+            let delay = {
+                let mut s = state.lock().await;
+                if s.rojo.restart_count >= policy.max_attempts {
+                    log::error!(
+                        "Rojo crashed {} time(s) in a row, giving up on automatic restarts",
+                        s.rojo.restart_count
+                    );
+                    s.rojo.status = RojoStatus::Error(user_msg);
+                    emit_status(&app, &s.rojo);
+                    return;
+                }
+                s.rojo.restart_count += 1;
+                s.rojo.status = RojoStatus::Restarting;
+                emit_status(&app, &s.rojo);
+                let delay = policy.backoff(s.rojo.restart_count);
+                log::warn!(
+                    "Restarting Rojo in {}ms (attempt {}/{})",
+                    delay.as_millis(),
+                    s.rojo.restart_count,
+                    policy.max_attempts
+                );
+                delay
+            };
+
+            tokio::time::sleep(delay).await;
+
+            {
+                let s = state.lock().await;
+                if s.rojo.status != RojoStatus::Restarting {
+                    log::info!(
+                        "Automatic Rojo restart cancelled (status is now {:?})",
+                        s.rojo.status
+                    );
+                    return;
+                }
+            }
+
+            match start(Arc::clone(&state), app.clone()).await {
+                Ok(()) => return,
+                Err(e) => {
+                    let s = state.lock().await;
+                    if !matches!(s.rojo.status, RojoStatus::Error(_)) {
+                        return;
+                    }
+                    user_msg = e;
+                }
+            }
+        }
+    });
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_rojo_status(
+    state: tauri::State<'_, SharedOpenCodeState>,
+) -> Result<RojoStatusPayload, String> {
+    Ok(payload(&state.lock().await.rojo))
+}
+
+/// Stop Rojo and start it again, picking up a `default.project.json` that
+/// was added or removed since OpenCode started.
+#[tauri::command]
+pub async fn restart_rojo(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+) -> Result<RojoStatusPayload, String> {
+    stop(state.inner(), &app).await;
+    state.lock().await.rojo.restart_count = 0;
+    start(state.inner().clone(), app).await?;
+    Ok(payload(&state.lock().await.rojo))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    use tokio::sync::Mutex;
+
+    use crate::opencode::OpenCodeState;
+    use crate::test_util::{GlobalConfig, TempDir};
+
+    /// Stands in for `rojo serve`: listens on `--port` until terminated.
+    const STUB_ROJO: &str = r#"#!/usr/bin/env python3
+import socket, sys
+port = int(sys.argv[sys.argv.index("--port") + 1])
+server = socket.socket()
+server.setsockopt(socket.SOL_SOCKET, socket.SO_REUSEADDR, 1)
+server.bind(("127.0.0.1", port))
+server.listen()
+print(f"Rojo server listening on port {port}", flush=True)
+while True:
+    server.accept()[0].close()
+"#;
+
+    #[tokio::test]
+    async fn stub_rojo_runs_and_stop_all_leaves_nothing_behind() {
+        let temp = TempDir::new("rojo");
+        let root = temp.path();
+        let workspace = root.join(crate::projects::DEFAULT_PROJECT);
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(
+            workspace.join(PROJECT_FILE),
+            r#"{"name":"Stub","tree":{"$className":"DataModel"}}"#,
+        )
+        .unwrap();
+        let binary = root.join("rojo");
+        std::fs::write(&binary, STUB_ROJO).unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut cfg = crate::config::AppConfig::default();
+        cfg.rojo.enabled = true;
+        cfg.rojo.path = Some(binary);
+        let _config = GlobalConfig::set(root, cfg);
+
+        let app = tauri::test::mock_builder()
+            .plugin(tauri_plugin_shell::init())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        let state: SharedOpenCodeState = Arc::new(Mutex::new(OpenCodeState::default()));
+
+        start(Arc::clone(&state), app.handle().clone())
+            .await
+            .unwrap();
+        let pid = {
+            let s = state.lock().await;
+            assert_eq!(s.rojo.status, RojoStatus::Running);
+            s.rojo.pid().unwrap()
+        };
+        assert!(crate::process_registry::is_alive(pid));
+
+        let report = crate::opencode::stop_all(&state, app.handle()).await;
+        assert_eq!(report.exited, [(ProcessRole::Rojo, pid)]);
+        assert!(report.killed.is_empty());
+        assert!(!crate::process_registry::is_alive(pid));
+        assert!(crate::process_registry::verify_owned().owned.is_empty());
+        assert_eq!(state.lock().await.rojo.status, RojoStatus::Stopped);
+    }
+}
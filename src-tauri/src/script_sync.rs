@@ -0,0 +1,992 @@
+//! Two-way mirror of Studio scripts.
+//!
+//! `sync_scripts` pulls every Script, LocalScript and ModuleScript of the
+//! connected place into `.luau` files under `<workspace>/studio/`, laid out
+//! like the Explorer and named with Rojo's suffixes:
+//!
+//! - `Script` → `Name.server.luau`
+//! - `LocalScript` → `Name.client.luau`
+//! - `ModuleScript` → `Name.luau`
+//!
+//! so the agent's file tools and git work on real files. From then on a
+//! watcher pushes local edits of those files back into Studio. Edits made
+//! in Studio are not watched: they come in with the next `sync_scripts`,
+//! so run it again after working in Studio.
+//!
+//! The mirror belongs to one place, recorded in the manifest. If Studio has
+//! another place open, syncs are refused and the watcher stops rather than
+//! pushing one place's scripts into the other; the watcher also checks the
+//! place every `PLACE_CHECK_INTERVAL`.
+//!
+//! Each side is compared with the content recorded at the last sync (kept
+//! in `.opencode/state/script-sync.json`). A script that changed on one
+//! side is copied to the other; one that changed on both is reported as a
+//! conflict and left alone until `resolve_script_conflict` picks a side.
+//! Creating and deleting scripts stays a Studio operation: a deleted file
+//! is restored from Studio, and a new file without a script is ignored.
+//!
+//! Studio is reached through the MCP bridge's `/mcp/<tool>` HTTP endpoints
+//! (`get_project_structure`, `get_script_source`, `set_script_source`),
+//! the same tools the agent calls.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+
+use crate::opencode::SharedOpenCodeState;
+use crate::studio_bridge::{bridge_port, call_tool, place_info, PlaceInfo};
+
+/// Folder in the workspace that holds the mirrored scripts.
+pub const MIRROR_DIR: &str = "studio";
+
+const MANIFEST_FILENAME: &str = "script-sync.json";
+
+const SCRIPT_CLASSES: &[&str] = &["Script", "LocalScript", "ModuleScript"];
+
+/// `get_project_structure` stops at depth 3 by default; scripts can sit
+/// much deeper than that.
+const MAX_DEPTH: u32 = 64;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often the watcher makes sure Studio still has the mirrored place
+/// open, besides before every push.
+const PLACE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+// ── Types ───────────────────────────────────────────────────────────────
+
+/// A script as of the last sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Tracked {
+    instance_path: String,
+    class_name: String,
+    /// `hash` of the source both sides had.
+    hash: u64,
+}
+
+/// Tracked scripts, keyed by file path relative to the workspace (always
+/// with `/` separators).
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    /// The place the scripts were mirrored from.
+    #[serde(default)]
+    place: Option<PlaceInfo>,
+    scripts: BTreeMap<String, Tracked>,
+}
+
+/// A script in the connected place and the file it is mirrored to.
+#[derive(Debug, Clone)]
+struct RemoteScript {
+    instance_path: String,
+    class_name: String,
+    rel_path: String,
+}
+
+/// A script that changed both in Studio and on disk since the last sync.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conflict {
+    pub path: String,
+    pub instance_path: String,
+    pub class_name: String,
+    /// The script is gone from Studio but the file was edited.
+    pub deleted_in_studio: bool,
+}
+
+/// Which side wins when resolving a conflict.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeepSide {
+    Local,
+    Studio,
+}
+
+/// What one sync did, by file path.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub pulled: Vec<String>,
+    pub pushed: Vec<String>,
+    pub removed: Vec<String>,
+    pub conflicts: Vec<Conflict>,
+    /// Scripts that could not be mirrored, with the reason.
+    pub skipped: Vec<String>,
+}
+
+/// Payload of `get_script_sync_status` and the `script-sync-changed` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub watching: bool,
+    pub mirror_dir: Option<String>,
+    pub tracked: usize,
+    pub conflicts: Vec<Conflict>,
+    pub last_error: Option<String>,
+}
+
+// ── State ───────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct SyncState {
+    /// Workspace whose mirror is being watched, if any.
+    workspace: Option<PathBuf>,
+    /// Bumped to stop the current watcher.
+    generation: u64,
+    conflicts: Vec<Conflict>,
+    last_error: Option<String>,
+}
+
+/// Also serialises syncs, so the watcher and the commands never work on
+/// the manifest at the same time.
+fn sync_state() -> &'static Mutex<SyncState> {
+    static STATE: OnceLock<Mutex<SyncState>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(SyncState::default()))
+}
+
+fn status(sync: &SyncState) -> SyncStatus {
+    SyncStatus {
+        watching: sync.workspace.is_some(),
+        mirror_dir: sync
+            .workspace
+            .as_ref()
+            .map(|w| w.join(MIRROR_DIR).to_string_lossy().to_string()),
+        tracked: sync
+            .workspace
+            .as_ref()
+            .map_or(0, |w| load_manifest(w).scripts.len()),
+        conflicts: sync.conflicts.clone(),
+        last_error: sync.last_error.clone(),
+    }
+}
+
+fn emit_status(app: &AppHandle, sync: &SyncState) {
+    let _ = app.emit("script-sync-changed", status(sync));
+}
+
+/// Stop the watcher and clear the conflicts, recording `error` if any.
+fn stop_watching(app: &AppHandle, sync: &mut SyncState, error: Option<String>) {
+    sync.generation += 1;
+    sync.workspace = None;
+    sync.conflicts.clear();
+    sync.last_error = error;
+    emit_status(app, sync);
+}
+
+/// Replace the conflicts for `paths` with `found`.
+fn update_conflicts(sync: &mut SyncState, paths: &[String], found: &[Conflict]) {
+    sync.conflicts.retain(|c| !paths.contains(&c.path));
+    sync.conflicts.extend_from_slice(found);
+}
+
+// ── Manifest and files ──────────────────────────────────────────────────
+
+fn manifest_path(workspace: &Path) -> PathBuf {
+    workspace
+        .join(".opencode")
+        .join("state")
+        .join(MANIFEST_FILENAME)
+}
+
+fn load_manifest(workspace: &Path) -> Manifest {
+    match std::fs::read(manifest_path(workspace)) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            log::warn!("Corrupt script sync manifest, starting over: {e}");
+            Manifest::default()
+        }),
+        Err(_) => Manifest::default(),
+    }
+}
+
+fn save_manifest(workspace: &Path, manifest: &Manifest) -> Result<(), String> {
+    let path = manifest_path(workspace);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize script sync manifest: {e}"))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// FNV-1a. Stable across runs and Rust versions, unlike `DefaultHasher`,
+/// which matters because hashes are persisted in the manifest.
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Characters that are not allowed in file names on at least one platform.
+const RESERVED_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// File for a script at `names` (Explorer path below `game`), or `None` if
+/// a name can't be used as a file name.
+fn mirror_path(names: &[String], class_name: &str) -> Option<String> {
+    let valid = |name: &String| {
+        !name.is_empty()
+            && name != "."
+            && name != ".."
+            && !name.contains(RESERVED_CHARS)
+            && !name.chars().any(char::is_control)
+    };
+    if !names.iter().all(valid) {
+        return None;
+    }
+    let (name, parents) = names.split_last()?;
+    let suffix = match class_name {
+        "Script" => ".server.luau",
+        "LocalScript" => ".client.luau",
+        _ => ".luau",
+    };
+    let mut path = MIRROR_DIR.to_string();
+    for parent in parents {
+        path.push('/');
+        path.push_str(parent);
+    }
+    Some(format!("{path}/{name}{suffix}"))
+}
+
+// ── Studio calls ─────────────────────────────────────────────────────────
+
+/// Walk a `get_project_structure` result and collect the scripts in it.
+/// Instances are recognised by their `name` and `className`; everything
+/// else is searched for nested instances.
+fn collect_scripts(
+    node: &Value,
+    names: &[String],
+    found: &mut Vec<RemoteScript>,
+    skipped: &mut Vec<String>,
+) {
+    match node {
+        Value::Array(items) => {
+            for item in items {
+                collect_scripts(item, names, found, skipped);
+            }
+        }
+        Value::Object(map) => {
+            let mut names = names.to_vec();
+            let class_name = map.get("className").and_then(Value::as_str);
+            if let (Some(name), Some(class_name)) =
+                (map.get("name").and_then(Value::as_str), class_name)
+            {
+                if class_name != "DataModel" {
+                    names.push(name.to_string());
+                }
+                if SCRIPT_CLASSES.contains(&class_name) {
+                    let instance_path = map
+                        .get("path")
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("game.{}", names.join(".")));
+                    match mirror_path(&names, class_name) {
+                        Some(rel_path) => found.push(RemoteScript {
+                            instance_path,
+                            class_name: class_name.to_string(),
+                            rel_path,
+                        }),
+                        None => {
+                            skipped.push(format!("{instance_path}: name is not a valid file name"))
+                        }
+                    }
+                }
+            }
+            for value in map.values() {
+                if value.is_array() || value.is_object() {
+                    collect_scripts(value, &names, found, skipped);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Keep the first script for each file. Paths are compared ignoring case,
+/// since `Main` and `main` are the same file on Windows and macOS.
+fn drop_duplicates(found: Vec<RemoteScript>, skipped: &mut Vec<String>) -> Vec<RemoteScript> {
+    let mut seen = HashMap::new();
+    let mut scripts = Vec::new();
+    for script in found {
+        match seen.get(&script.rel_path.to_lowercase()) {
+            Some(kept) => skipped.push(format!(
+                "{}: another script maps to {kept}",
+                script.instance_path
+            )),
+            None => {
+                seen.insert(script.rel_path.to_lowercase(), script.rel_path.clone());
+                scripts.push(script);
+            }
+        }
+    }
+    scripts
+}
+
+/// All scripts in the connected place. Scripts that can't be mirrored
+/// (bad names, two siblings whose names differ only in case) are returned
+/// as skipped.
+async fn list_scripts(port: u16) -> Result<(Vec<RemoteScript>, Vec<String>), String> {
+    let structure = call_tool(
+        port,
+        "get_project_structure",
+        json!({ "maxDepth": MAX_DEPTH, "scriptsOnly": true }),
+    )
+    .await?;
+    let mut found = Vec::new();
+    let mut skipped = Vec::new();
+    collect_scripts(&structure, &[], &mut found, &mut skipped);
+    let scripts = drop_duplicates(found, &mut skipped);
+    Ok((scripts, skipped))
+}
+
+async fn studio_source(port: u16, instance_path: &str) -> Result<String, String> {
+    call_tool(
+        port,
+        "get_script_source",
+        json!({ "instancePath": instance_path }),
+    )
+    .await?
+    .get("source")
+    .and_then(Value::as_str)
+    .map(str::to_string)
+    .ok_or_else(|| format!("get_script_source returned no source for {instance_path}"))
+}
+
+async fn push_source(port: u16, instance_path: &str, source: &str) -> Result<(), String> {
+    call_tool(
+        port,
+        "set_script_source",
+        json!({ "instancePath": instance_path, "source": source }),
+    )
+    .await
+    .map(|_| ())
+}
+
+// ── Sync ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    /// Studio → file.
+    Pull,
+    /// File → Studio.
+    Push,
+    /// Both sides agree; only the manifest is updated.
+    Record,
+    Conflict,
+}
+
+/// What to do given the hash at the last sync and the current hashes.
+/// A missing file is restored from Studio.
+fn decide(base: Option<u64>, local: Option<u64>, studio: u64) -> Action {
+    let Some(local) = local else {
+        return Action::Pull;
+    };
+    match base {
+        _ if local == studio => Action::Record,
+        Some(base) if local == base => Action::Pull,
+        Some(base) if studio == base => Action::Push,
+        _ => Action::Conflict,
+    }
+}
+
+/// Bring one script's file and Studio source in line.
+async fn sync_one(
+    port: u16,
+    workspace: &Path,
+    script: &RemoteScript,
+    manifest: &mut Manifest,
+    report: &mut SyncReport,
+) -> Result<(), String> {
+    let file = workspace.join(&script.rel_path);
+    let studio = studio_source(port, &script.instance_path).await?;
+    let local = std::fs::read(&file).ok();
+    let base = manifest.scripts.get(&script.rel_path).map(|t| t.hash);
+
+    let mut synced = hash(studio.as_bytes());
+    match decide(base, local.as_deref().map(hash), synced) {
+        Action::Record => {}
+        Action::Pull => {
+            write_file(&file, &studio)?;
+            report.pulled.push(script.rel_path.clone());
+        }
+        Action::Push => {
+            let source = String::from_utf8(local.unwrap_or_default())
+                .map_err(|_| format!("{} is not valid UTF-8", file.display()))?;
+            push_source(port, &script.instance_path, &source).await?;
+            synced = hash(source.as_bytes());
+            report.pushed.push(script.rel_path.clone());
+        }
+        Action::Conflict => {
+            report.conflicts.push(Conflict {
+                path: script.rel_path.clone(),
+                instance_path: script.instance_path.clone(),
+                class_name: script.class_name.clone(),
+                deleted_in_studio: false,
+            });
+            return Ok(());
+        }
+    }
+    manifest.scripts.insert(
+        script.rel_path.clone(),
+        Tracked {
+            instance_path: script.instance_path.clone(),
+            class_name: script.class_name.clone(),
+            hash: synced,
+        },
+    );
+    Ok(())
+}
+
+/// Error unless `open` is the place the mirror in `manifest` belongs to.
+/// A mirror that tracks nothing belongs to no place yet.
+fn check_place(manifest: &Manifest, open: &PlaceInfo) -> Result<(), String> {
+    match &manifest.place {
+        Some(mirrored) if !manifest.scripts.is_empty() && !mirrored.same_place(open) => Err(
+            format!("The {MIRROR_DIR}/ mirror belongs to {mirrored}, but Studio has {open} open"),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Reconcile every script in the place with the mirror.
+async fn full_sync(port: u16, workspace: &Path) -> Result<SyncReport, String> {
+    let place = place_info(port).await?;
+    let mut manifest = load_manifest(workspace);
+    check_place(&manifest, &place).map_err(|e| {
+        format!("{e}. Open that place, or delete {MIRROR_DIR}/ to mirror this one instead")
+    })?;
+    manifest.place = Some(place);
+    let (scripts, skipped) = list_scripts(port).await?;
+    let mut report = SyncReport {
+        skipped,
+        ..SyncReport::default()
+    };
+
+    for script in &scripts {
+        if let Err(e) = sync_one(port, workspace, script, &mut manifest, &mut report).await {
+            log::warn!("Failed to sync {}: {e}", script.instance_path);
+            report
+                .skipped
+                .push(format!("{}: {e}", script.instance_path));
+        }
+    }
+
+    // Scripts deleted in Studio: drop their files unless they were edited.
+    let live: HashSet<&str> = scripts.iter().map(|s| s.rel_path.as_str()).collect();
+    let gone: Vec<String> = manifest
+        .scripts
+        .keys()
+        .filter(|path| !live.contains(path.as_str()))
+        .cloned()
+        .collect();
+    for path in gone {
+        let tracked = manifest.scripts[&path].clone();
+        let file = workspace.join(&path);
+        match std::fs::read(&file) {
+            Ok(bytes) if hash(&bytes) != tracked.hash => {
+                report.conflicts.push(Conflict {
+                    path,
+                    instance_path: tracked.instance_path,
+                    class_name: tracked.class_name,
+                    deleted_in_studio: true,
+                });
+                continue;
+            }
+            Ok(_) => {
+                std::fs::remove_file(&file)
+                    .map_err(|e| format!("Failed to delete {}: {e}", file.display()))?;
+                report.removed.push(path.clone());
+            }
+            Err(_) => {}
+        }
+        manifest.scripts.remove(&path);
+    }
+
+    save_manifest(workspace, &manifest)?;
+    Ok(report)
+}
+
+/// Modification time and size of every tracked file.
+type Fingerprint = BTreeMap<String, Option<(Option<SystemTime>, u64)>>;
+
+fn fingerprint(workspace: &Path) -> Fingerprint {
+    load_manifest(workspace)
+        .scripts
+        .into_keys()
+        .map(|path| {
+            let meta = std::fs::metadata(workspace.join(&path)).ok();
+            let stamp = meta.map(|m| (m.modified().ok(), m.len()));
+            (path, stamp)
+        })
+        .collect()
+}
+
+/// Stop watcher `generation` if Studio no longer has the mirrored place
+/// open. Does nothing while Studio can't be asked.
+async fn verify_place(
+    state: &SharedOpenCodeState,
+    app: &AppHandle,
+    workspace: &Path,
+    generation: u64,
+) -> Result<(), String> {
+    let Ok(port) = bridge_port(state).await else {
+        return Ok(());
+    };
+    let Ok(open) = place_info(port).await else {
+        return Ok(());
+    };
+    let mut sync = sync_state().lock().await;
+    if sync.generation != generation {
+        return Ok(());
+    }
+    if let Err(e) = check_place(&load_manifest(workspace), &open) {
+        let e = format!("{e}; stopped syncing");
+        stop_watching(app, &mut sync, Some(e.clone()));
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Sync the tracked files in `paths` after they changed on disk.
+async fn sync_changed(
+    state: &SharedOpenCodeState,
+    app: &AppHandle,
+    workspace: &Path,
+    paths: &[String],
+    generation: u64,
+) -> Result<(), String> {
+    verify_place(state, app, workspace, generation).await?;
+    let mut sync = sync_state().lock().await;
+    if sync.generation != generation {
+        return Ok(());
+    }
+    let port = bridge_port(state).await?;
+    let mut manifest = load_manifest(workspace);
+    let mut report = SyncReport::default();
+    let mut result = Ok(());
+    for path in paths {
+        let Some(tracked) = manifest.scripts.get(path).cloned() else {
+            continue;
+        };
+        let script = RemoteScript {
+            instance_path: tracked.instance_path,
+            class_name: tracked.class_name,
+            rel_path: path.clone(),
+        };
+        if let Err(e) = sync_one(port, workspace, &script, &mut manifest, &mut report).await {
+            result = Err(format!("Failed to sync {path}: {e}"));
+            break;
+        }
+    }
+    save_manifest(workspace, &manifest)?;
+
+    for path in &report.pushed {
+        log::info!("Pushed {path} to Studio");
+    }
+    for path in &report.pulled {
+        log::info!("Restored {path} from Studio");
+    }
+    for conflict in &report.conflicts {
+        log::warn!("Conflict: {} changed in Studio and on disk", conflict.path);
+    }
+    update_conflicts(&mut sync, paths, &report.conflicts);
+    sync.last_error = result.as_ref().err().cloned();
+    emit_status(app, &sync);
+    result
+}
+
+/// Watch the mirrored files and push local edits until `generation` is
+/// superseded or the workspace changes.
+fn spawn_watcher(app: AppHandle, workspace: PathBuf, generation: u64) {
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<SharedOpenCodeState>().inner().clone();
+        let mut last = fingerprint(&workspace);
+        let mut place_checked = Instant::now();
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+            if sync_state().lock().await.generation != generation {
+                log::debug!("Script sync watcher {generation} exiting");
+                return;
+            }
+            if crate::paths::workspace_dir().ok().as_ref() != Some(&workspace) {
+                log::info!("Workspace changed; stopping script sync");
+                let mut sync = sync_state().lock().await;
+                if sync.generation == generation {
+                    let error = sync.last_error.take();
+                    stop_watching(&app, &mut sync, error);
+                }
+                return;
+            }
+            if place_checked.elapsed() >= PLACE_CHECK_INTERVAL {
+                place_checked = Instant::now();
+                if let Err(e) = verify_place(&state, &app, &workspace, generation).await {
+                    log::warn!("Script sync: {e}");
+                    return;
+                }
+            }
+
+            let current = fingerprint(&workspace);
+            let changed: Vec<String> = current
+                .iter()
+                .filter(|(path, stamp)| last.get(*path) != Some(*stamp))
+                .map(|(path, _)| path.clone())
+                .collect();
+            if changed.is_empty() {
+                last = current;
+                continue;
+            }
+            // Only move on once the change reached Studio, so a failed push
+            // (Studio disconnected) is retried on the next tick.
+            match sync_changed(&state, &app, &workspace, &changed, generation).await {
+                Ok(()) => last = fingerprint(&workspace),
+                Err(e) => log::warn!("Script sync: {e}"),
+            }
+        }
+    });
+}
+
+/// Stop the watcher, keeping the conflicts. Returns the workspace it was
+/// watching, for `resume`.
+pub async fn pause(app: &AppHandle) -> Option<PathBuf> {
+    let mut sync = sync_state().lock().await;
+    sync.generation += 1;
+    let workspace = sync.workspace.take();
+    emit_status(app, &sync);
+    workspace
+}
+
+/// Start watching `workspace` again after `pause`.
+pub async fn resume(app: &AppHandle, workspace: PathBuf) {
+    let mut sync = sync_state().lock().await;
+    sync.generation += 1;
+    sync.workspace = Some(workspace.clone());
+    spawn_watcher(app.clone(), workspace, sync.generation);
+    emit_status(app, &sync);
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+/// Mirror all scripts of the connected place into the workspace and start
+/// pushing local edits back.
+#[tauri::command]
+pub async fn sync_scripts(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+) -> Result<SyncReport, String> {
+    let port = bridge_port(state.inner()).await?;
+    let workspace = crate::paths::workspace_dir()?;
+    let mut sync = sync_state().lock().await;
+
+    let report = match full_sync(port, &workspace).await {
+        Ok(report) => report,
+        Err(e) => {
+            sync.last_error = Some(e.clone());
+            emit_status(&app, &sync);
+            return Err(e);
+        }
+    };
+    log::info!(
+        "Script sync: {} pulled, {} pushed, {} removed, {} conflict(s), {} skipped",
+        report.pulled.len(),
+        report.pushed.len(),
+        report.removed.len(),
+        report.conflicts.len(),
+        report.skipped.len()
+    );
+    sync.conflicts = report.conflicts.clone();
+    sync.last_error = None;
+    if sync.workspace.as_ref() != Some(&workspace) {
+        sync.generation += 1;
+        sync.workspace = Some(workspace.clone());
+        spawn_watcher(app.clone(), workspace, sync.generation);
+    }
+    emit_status(&app, &sync);
+    Ok(report)
+}
+
+/// Stop pushing local edits to Studio. The mirrored files stay.
+#[tauri::command]
+pub async fn stop_script_sync(app: AppHandle) -> Result<SyncStatus, String> {
+    let mut sync = sync_state().lock().await;
+    let error = sync.last_error.take();
+    stop_watching(&app, &mut sync, error);
+    Ok(status(&sync))
+}
+
+#[tauri::command]
+pub async fn get_script_sync_status() -> Result<SyncStatus, String> {
+    Ok(status(&*sync_state().lock().await))
+}
+
+/// Settle a conflict by copying one side over the other.
+#[tauri::command]
+pub async fn resolve_script_conflict(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+    path: String,
+    keep: KeepSide,
+) -> Result<SyncStatus, String> {
+    let port = bridge_port(state.inner()).await?;
+    let workspace = crate::paths::workspace_dir()?;
+    let open = place_info(port).await?;
+    let mut sync = sync_state().lock().await;
+    check_place(&load_manifest(&workspace), &open)?;
+    let conflict = sync
+        .conflicts
+        .iter()
+        .find(|c| c.path == path)
+        .cloned()
+        .ok_or_else(|| format!("No conflict for {path}"))?;
+
+    let mut manifest = load_manifest(&workspace);
+    let file = workspace.join(&conflict.path);
+    let synced = match (keep, conflict.deleted_in_studio) {
+        (KeepSide::Studio, true) => {
+            if file.exists() {
+                std::fs::remove_file(&file)
+                    .map_err(|e| format!("Failed to delete {}: {e}", file.display()))?;
+            }
+            None
+        }
+        // The file stays as an untracked local copy.
+        (KeepSide::Local, true) => None,
+        (KeepSide::Studio, false) => {
+            let source = studio_source(port, &conflict.instance_path).await?;
+            write_file(&file, &source)?;
+            Some(hash(source.as_bytes()))
+        }
+        (KeepSide::Local, false) => {
+            let source = std::fs::read_to_string(&file)
+                .map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+            push_source(port, &conflict.instance_path, &source).await?;
+            Some(hash(source.as_bytes()))
+        }
+    };
+    match synced {
+        Some(hash) => {
+            manifest.scripts.insert(
+                conflict.path.clone(),
+                Tracked {
+                    instance_path: conflict.instance_path.clone(),
+                    class_name: conflict.class_name.clone(),
+                    hash,
+                },
+            );
+        }
+        None => {
+            manifest.scripts.remove(&conflict.path);
+        }
+    }
+    save_manifest(&workspace, &manifest)?;
+    log::info!("Resolved conflict on {path} keeping the {keep:?} version");
+
+    sync.conflicts.retain(|c| c.path != path);
+    emit_status(&app, &sync);
+    Ok(status(&sync))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(place_id: u64, name: &str) -> PlaceInfo {
+        PlaceInfo {
+            place_id,
+            name: name.to_string(),
+        }
+    }
+
+    fn mirror_of(place: PlaceInfo) -> Manifest {
+        let mut manifest = Manifest {
+            place: Some(place),
+            ..Manifest::default()
+        };
+        manifest.scripts.insert(
+            "studio/ServerScriptService/Main.server.luau".to_string(),
+            Tracked {
+                instance_path: "game.ServerScriptService.Main".to_string(),
+                class_name: "Script".to_string(),
+                hash: hash(b"print(1)"),
+            },
+        );
+        manifest
+    }
+
+    #[test]
+    fn check_place_rejects_another_place() {
+        let manifest = mirror_of(place(1818, "Crossroads"));
+        assert!(check_place(&manifest, &place(1818, "Crossroads (edited)")).is_ok());
+        let err = check_place(&manifest, &place(920587237, "Adopt Me")).unwrap_err();
+        assert!(
+            err.contains("place 1818") && err.contains("place 920587237"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn check_place_matches_unpublished_places_by_name() {
+        let manifest = mirror_of(place(0, "Prototype"));
+        assert!(check_place(&manifest, &place(0, "Prototype")).is_ok());
+        // Publishing keeps it the same place.
+        assert!(check_place(&manifest, &place(4242, "Prototype")).is_ok());
+        assert!(check_place(&manifest, &place(0, "Baseplate")).is_err());
+    }
+
+    #[test]
+    fn empty_mirror_belongs_to_no_place() {
+        let manifest = Manifest {
+            place: Some(place(1818, "Crossroads")),
+            ..Manifest::default()
+        };
+        assert!(check_place(&manifest, &place(920587237, "Adopt Me")).is_ok());
+        assert!(check_place(&Manifest::default(), &place(1818, "Crossroads")).is_ok());
+    }
+
+    #[test]
+    fn decide_covers_every_case() {
+        let (old, new, other) = (1, 2, 3);
+        let cases = [
+            // (base, local, studio, expected)
+            (None, None, new, Action::Pull),
+            (Some(old), None, new, Action::Pull),
+            (None, Some(new), new, Action::Record),
+            (None, Some(other), new, Action::Conflict),
+            (Some(old), Some(old), old, Action::Record),
+            (Some(old), Some(old), new, Action::Pull),
+            (Some(old), Some(new), old, Action::Push),
+            (Some(old), Some(new), new, Action::Record),
+            (Some(old), Some(new), other, Action::Conflict),
+        ];
+        for (base, local, studio, expected) in cases {
+            assert_eq!(
+                decide(base, local, studio),
+                expected,
+                "base {base:?}, local {local:?}, studio {studio}"
+            );
+        }
+    }
+
+    fn names(path: &[&str]) -> Vec<String> {
+        path.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn mirror_path_uses_class_suffixes() {
+        let main = names(&["ServerScriptService", "Main"]);
+        assert_eq!(
+            mirror_path(&main, "Script").as_deref(),
+            Some("studio/ServerScriptService/Main.server.luau")
+        );
+        assert_eq!(
+            mirror_path(&main, "LocalScript").as_deref(),
+            Some("studio/ServerScriptService/Main.client.luau")
+        );
+        assert_eq!(
+            mirror_path(&main, "ModuleScript").as_deref(),
+            Some("studio/ServerScriptService/Main.luau")
+        );
+    }
+
+    #[test]
+    fn mirror_path_rejects_reserved_names() {
+        for bad in ["", ".", "..", "a/b", "a\\b", "Why?", "C:", "tab\there"] {
+            assert_eq!(
+                mirror_path(&names(&["Workspace", bad]), "Script"),
+                None,
+                "{bad:?}"
+            );
+            assert_eq!(
+                mirror_path(&names(&[bad, "Main"]), "Script"),
+                None,
+                "{bad:?}"
+            );
+        }
+        assert!(mirror_path(&names(&["Workspace", "My Script.v2"]), "Script").is_some());
+    }
+
+    #[test]
+    fn collect_scripts_walks_nested_instances() {
+        let structure = json!({
+            "name": "Game",
+            "className": "DataModel",
+            "children": [
+                {
+                    "name": "ServerScriptService",
+                    "className": "ServerScriptService",
+                    "children": [
+                        { "name": "Main", "className": "Script", "path": "game.ServerScriptService.Main" },
+                        { "name": "Bad/Name", "className": "ModuleScript" },
+                        { "name": "Folder", "className": "Folder", "children": [
+                            { "name": "Util", "className": "ModuleScript" }
+                        ] }
+                    ]
+                },
+                { "name": "Part", "className": "Part" }
+            ]
+        });
+        let (mut found, mut skipped) = (Vec::new(), Vec::new());
+        collect_scripts(&structure, &[], &mut found, &mut skipped);
+
+        let mut paths: Vec<_> = found
+            .iter()
+            .map(|s| (s.instance_path.as_str(), s.rel_path.as_str()))
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                (
+                    "game.ServerScriptService.Folder.Util",
+                    "studio/ServerScriptService/Folder/Util.luau"
+                ),
+                (
+                    "game.ServerScriptService.Main",
+                    "studio/ServerScriptService/Main.server.luau"
+                ),
+            ]
+        );
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].starts_with("game.ServerScriptService.Bad/Name:"));
+    }
+
+    #[test]
+    fn duplicates_are_dropped_ignoring_case() {
+        let script = |instance_path: &str, rel_path: &str| RemoteScript {
+            instance_path: instance_path.to_string(),
+            class_name: "ModuleScript".to_string(),
+            rel_path: rel_path.to_string(),
+        };
+        let mut skipped = Vec::new();
+        let kept = drop_duplicates(
+            vec![
+                script("game.Workspace.Util", "studio/Workspace/Util.luau"),
+                script("game.Workspace.util", "studio/Workspace/util.luau"),
+                script("game.Workspace.Util", "studio/Workspace/Util.luau"),
+                script("game.Workspace.Other", "studio/Workspace/Other.luau"),
+            ],
+            &mut skipped,
+        );
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].instance_path, "game.Workspace.Util");
+        assert_eq!(
+            skipped,
+            [
+                "game.Workspace.util: another script maps to studio/Workspace/Util.luau",
+                "game.Workspace.Util: another script maps to studio/Workspace/Util.luau",
+            ]
+        );
+    }
+}
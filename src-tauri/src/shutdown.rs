@@ -0,0 +1,164 @@
+//! Graceful shutdown coordinator.
+//!
+//! Every way the app can exit — closing the main window, the app menu's
+//! Quit item, the `process` plugin's `exit`/`relaunch` (used after an
+//! update), and SIGTERM/SIGINT/Ctrl+C — ends up in
+//! `RunEvent::ExitRequested`. This module intercepts that event exactly
+//! once, tears down the OpenCode process tree via `opencode::stop_all`,
+//! logs a shutdown report, and then lets the exit continue.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager, RunEvent};
+
+use crate::opencode::SharedOpenCodeState;
+
+const IDLE: u8 = 0;
+const IN_PROGRESS: u8 = 1;
+const DONE: u8 = 2;
+
+/// Where the coordinator is. Only the caller that moves it from `IDLE` to
+/// `IN_PROGRESS` runs the shutdown.
+static PHASE: AtomicU8 = AtomicU8::new(IDLE);
+
+/// Why the exit was requested, when we know better than the exit code
+/// (e.g. an OS signal). Consumed by the coordinator for the report.
+static REASON: Mutex<Option<String>> = Mutex::new(None);
+
+/// Upper bound for waiting on an in-flight shutdown when an exit that
+/// can't be prevented (a restart) arrives in the meantime.
+const MAX_WAIT: Duration = Duration::from_secs(10);
+
+// ── Public API ──────────────────────────────────────────────────────────
+
+/// Exit the app for `reason`, going through the shutdown coordinator.
+pub fn request_exit(app: &AppHandle, reason: &str) {
+    if let Ok(mut guard) = REASON.lock() {
+        guard.get_or_insert_with(|| reason.to_string());
+    }
+    app.exit(0);
+}
+
+/// `RunEvent` callback passed to `App::run`.
+pub fn handle_run_event(app: &AppHandle, event: RunEvent) {
+    match event {
+        RunEvent::ExitRequested { code, api, .. } => {
+            if PHASE.load(Ordering::SeqCst) == DONE {
+                // Our own `exit()` after the shutdown finished.
+                return;
+            }
+
+            if code == Some(tauri::RESTART_EXIT_CODE) {
+                // Restarts can't be prevented, so block until the process
+                // tree is down.
+                run_blocking(app, "restart");
+                return;
+            }
+
+            api.prevent_exit();
+            if !begin() {
+                // Already shutting down; that task exits when it's done.
+                return;
+            }
+            let trigger = take_reason().unwrap_or_else(|| match code {
+                Some(c) => format!("exit({c})"),
+                None => "user quit".to_string(),
+            });
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                shutdown(&handle, &trigger).await;
+                handle.exit(code.unwrap_or(0));
+            });
+        }
+        // Backstop for exit paths that skipped `ExitRequested`.
+        RunEvent::Exit => run_blocking(app, "exit"),
+        _ => {}
+    }
+}
+
+/// Turn SIGTERM/SIGINT (Ctrl+C on Windows) into a coordinated exit.
+/// Call once from `setup`.
+pub fn listen_for_signals(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        #[cfg(unix)]
+        let name = {
+            use tokio::signal::unix::{signal, SignalKind};
+            let (Ok(mut term), Ok(mut int)) = (
+                signal(SignalKind::terminate()),
+                signal(SignalKind::interrupt()),
+            ) else {
+                log::warn!("Failed to install signal handlers");
+                return;
+            };
+            tokio::select! {
+                _ = term.recv() => "SIGTERM",
+                _ = int.recv() => "SIGINT",
+            }
+        };
+
+        #[cfg(windows)]
+        let name = {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                log::warn!("Failed to install Ctrl+C handler: {e}");
+                return;
+            }
+            "Ctrl+C"
+        };
+
+        log::info!("Received {name}");
+        request_exit(&app, name);
+    });
+}
+
+// ── Internals ───────────────────────────────────────────────────────────
+
+/// Claim the shutdown. Returns `false` if it already started.
+fn begin() -> bool {
+    PHASE
+        .compare_exchange(IDLE, IN_PROGRESS, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+}
+
+fn take_reason() -> Option<String> {
+    REASON.lock().ok().and_then(|mut guard| guard.take())
+}
+
+/// Run the shutdown on the current thread, or wait for an in-flight one.
+fn run_blocking(app: &AppHandle, fallback_trigger: &str) {
+    if begin() {
+        let trigger = take_reason().unwrap_or_else(|| fallback_trigger.to_string());
+        tauri::async_runtime::block_on(shutdown(app, &trigger));
+        return;
+    }
+    let waited = Instant::now();
+    while PHASE.load(Ordering::SeqCst) != DONE && waited.elapsed() < MAX_WAIT {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Stop the whole process tree and log what it took.
+async fn shutdown(app: &AppHandle, trigger: &str) {
+    let started = Instant::now();
+    log::info!("Shutting down ({trigger})");
+
+    let state = app.state::<SharedOpenCodeState>().inner().clone();
+    state.lock().await.shutting_down = true;
+    let report = crate::opencode::stop_all(&state, app).await;
+
+    log::info!(
+        "Shutdown report ({trigger}): {} process(es) exited gracefully, {} force-killed, took {}ms",
+        report.exited.len(),
+        report.killed.len(),
+        started.elapsed().as_millis()
+    );
+    for (role, pid) in &report.exited {
+        log::info!("  exited: {role:?} PID {pid}");
+    }
+    for (role, pid) in &report.killed {
+        log::warn!("  killed: {role:?} PID {pid}");
+    }
+
+    PHASE.store(DONE, Ordering::SeqCst);
+}
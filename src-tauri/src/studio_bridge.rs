@@ -0,0 +1,119 @@
+//! Calls into Roblox Studio through the MCP bridge.
+//!
+//! The roblox-studio MCP server exposes every tool as
+//! `POST http://127.0.0.1:<bridge port>/mcp/<tool>`, which the Studio plugin
+//! serves from its polling loop. BloxBot uses the same endpoints as the
+//! agent for the few things it needs from Studio itself: script sync and
+//! the place the plugin is connected to.
+
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::opencode::{OpenCodeStatus, SharedOpenCodeState, LOOPBACK};
+
+/// Every bridge call is a round trip through the Studio plugin's polling
+/// loop, so allow much more than for local HTTP calls.
+const BRIDGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The place open in the connected Studio.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceInfo {
+    /// `0` for a place that was never published.
+    pub place_id: u64,
+    pub name: String,
+}
+
+impl PlaceInfo {
+    /// Whether both are the same place. Unpublished places only have their
+    /// name to go by, so publishing a place keeps it the same.
+    pub fn same_place(&self, other: &PlaceInfo) -> bool {
+        if self.place_id != 0 && other.place_id != 0 {
+            self.place_id == other.place_id
+        } else {
+            self.name == other.name
+        }
+    }
+}
+
+impl fmt::Display for PlaceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.place_id {
+            0 => write!(f, "\"{}\" (unpublished)", self.name),
+            id => write!(f, "\"{}\" (place {id})", self.name),
+        }
+    }
+}
+
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(BRIDGE_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// The bridge port, if OpenCode (and with it the MCP server) is up.
+pub async fn bridge_port(state: &SharedOpenCodeState) -> Result<u16, String> {
+    let s = state.lock().await;
+    if !matches!(s.status, OpenCodeStatus::Running | OpenCodeStatus::Degraded) || s.mcp_port == 0 {
+        return Err("OpenCode is not running".to_string());
+    }
+    Ok(s.mcp_port)
+}
+
+/// The text content of an MCP tool result.
+fn tool_text(body: &Value) -> Option<String> {
+    body.get("content")?
+        .as_array()?
+        .iter()
+        .find_map(|c| c.get("text")?.as_str())
+        .map(str::to_string)
+}
+
+/// Call an MCP tool through the bridge. Returns the tool's JSON result, or
+/// its text if that is not JSON.
+pub async fn call_tool(port: u16, tool: &str, args: Value) -> Result<Value, String> {
+    let url = format!("http://{LOOPBACK}:{port}/mcp/{tool}");
+    let resp = http_client()
+        .post(&url)
+        .json(&args)
+        .send()
+        .await
+        .map_err(|e| format!("{tool}: {e}"))?;
+    let status = resp.status();
+    let body: Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("{tool} returned invalid JSON: {e}"))?;
+    if !status.is_success() || body.get("isError").and_then(Value::as_bool) == Some(true) {
+        let msg = body
+            .get("error")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| tool_text(&body))
+            .unwrap_or_else(|| format!("HTTP {status}"));
+        return Err(format!("{tool} failed: {msg}"));
+    }
+    let text = tool_text(&body).ok_or_else(|| format!("{tool} returned no content"))?;
+    Ok(serde_json::from_str(&text).unwrap_or(Value::String(text)))
+}
+
+/// The place open in Studio, from `get_place_info`.
+pub async fn place_info(port: u16) -> Result<PlaceInfo, String> {
+    let info = call_tool(port, "get_place_info", json!({})).await?;
+    let name = ["placeName", "name"]
+        .iter()
+        .find_map(|key| info.get(*key)?.as_str())
+        .ok_or_else(|| "get_place_info returned no place name".to_string())?;
+    Ok(PlaceInfo {
+        place_id: info.get("placeId").and_then(Value::as_u64).unwrap_or(0),
+        name: name.to_string(),
+    })
+}
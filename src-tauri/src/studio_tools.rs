@@ -0,0 +1,382 @@
+//! Catalogue of the tools exposed by the roblox-studio MCP server.
+//!
+//! OpenCode registers MCP tools as `<server>_<tool>`, so `set_property`
+//! shows up as `roblox-studio_set_property` in agent `tools` maps and in
+//! permission rules. Each tool is classified by whether it can change the
+//! place (or the running playtest); the read-only agent denies every tool
+//! and re-enables only the ones classified as read-only.
+//!
+//! `AppConfig::tool_policy` maps tool names or globs to allow/ask/deny.
+//! Globs are expanded against this catalogue when `opencode.json` is
+//! written, so every known tool gets one explicit rule and the precedence
+//! between overlapping patterns is decided here: an exact name beats any
+//! glob, and among globs the one with more literal characters wins. The
+//! globs themselves are written too, so they still cover tools a newer
+//! MCP server adds before the catalogue knows about them.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::config::ToolAction;
+use crate::opencode::SharedOpenCodeState;
+use crate::opencode_config::PermissionRule;
+
+/// Key of our MCP server in the generated `mcp` config.
+pub const MCP_SERVER_NAME: &str = "roblox-studio";
+
+/// One tool of the roblox-studio MCP server.
+#[derive(Debug, Clone, Copy)]
+pub struct StudioTool {
+    pub name: &'static str,
+    /// `true` if the tool creates, changes or deletes anything in Studio,
+    /// runs code, or controls playtests.
+    pub mutates: bool,
+}
+
+const fn read(name: &'static str) -> StudioTool {
+    StudioTool {
+        name,
+        mutates: false,
+    }
+}
+
+const fn write(name: &'static str) -> StudioTool {
+    StudioTool {
+        name,
+        mutates: true,
+    }
+}
+
+pub const STUDIO_TOOLS: &[StudioTool] = &[
+    // Project and instance inspection
+    read("get_file_tree"),
+    read("search_files"),
+    read("get_place_info"),
+    read("get_services"),
+    read("search_objects"),
+    read("get_instance_properties"),
+    read("get_instance_children"),
+    read("search_by_property"),
+    read("get_class_info"),
+    read("get_project_structure"),
+    read("get_selection"),
+    read("mass_get_property"),
+    // Properties
+    write("set_property"),
+    write("mass_set_property"),
+    write("set_calculated_property"),
+    write("set_relative_property"),
+    // Instances
+    write("create_object"),
+    write("create_object_with_properties"),
+    write("mass_create_objects"),
+    write("mass_create_objects_with_properties"),
+    write("delete_object"),
+    write("smart_duplicate"),
+    write("mass_duplicate"),
+    // Scripts
+    read("get_script_source"),
+    write("set_script_source"),
+    write("edit_script_lines"),
+    write("insert_script_lines"),
+    write("delete_script_lines"),
+    // Attributes and tags
+    read("get_attribute"),
+    read("get_attributes"),
+    write("set_attribute"),
+    write("delete_attribute"),
+    read("get_tags"),
+    read("get_tagged"),
+    write("add_tag"),
+    write("remove_tag"),
+    // Code execution and playtests
+    write("execute_luau"),
+    write("start_playtest"),
+    write("stop_playtest"),
+    read("get_playtest_output"),
+];
+
+/// The name OpenCode uses for `tool` (e.g. `roblox-studio_set_property`).
+pub fn opencode_tool_id(tool: &str) -> String {
+    format!("{MCP_SERVER_NAME}_{tool}")
+}
+
+/// Names of all tools that only read.
+pub fn read_only() -> impl Iterator<Item = &'static str> {
+    STUDIO_TOOLS.iter().filter(|t| !t.mutates).map(|t| t.name)
+}
+
+// ── Policy ──────────────────────────────────────────────────────────────
+
+/// A catalogue tool with the policy that applies to it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolPolicyEntry {
+    pub name: &'static str,
+    pub mutates: bool,
+    /// `None` if no rule matches; OpenCode then runs the tool without
+    /// asking.
+    pub action: Option<ToolAction>,
+    /// The `tool_policy` key that decided `action`.
+    pub matched_by: Option<String>,
+}
+
+/// Accept bare tool names as well as OpenCode IDs (`roblox-studio_*`).
+fn strip_server_prefix(pattern: &str) -> &str {
+    pattern
+        .strip_prefix(MCP_SERVER_NAME)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .unwrap_or(pattern)
+}
+
+pub fn validate_pattern(pattern: &str) -> Result<(), String> {
+    let bare = strip_server_prefix(pattern);
+    if bare.is_empty()
+        || !bare
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '*' | '?'))
+    {
+        return Err(format!(
+            "Tool pattern \"{pattern}\" may only contain lowercase letters, digits, '_', '*' and '?'"
+        ));
+    }
+    if !STUDIO_TOOLS.iter().any(|t| glob_match(bare, t.name)) {
+        log::warn!("Tool pattern \"{pattern}\" does not match any known roblox-studio tool");
+    }
+    Ok(())
+}
+
+/// `*` matches any run of characters, `?` exactly one.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let (p, n) = (pattern.as_bytes(), name.as_bytes());
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        match p.get(pi) {
+            Some(b'*') => {
+                backtrack = Some((pi, ni));
+                pi += 1;
+            }
+            Some(&c) if c == b'?' || c == n[ni] => {
+                pi += 1;
+                ni += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    pi = star + 1;
+                    ni = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == b'*')
+}
+
+/// How specific a pattern is: exact names first, then globs by their
+/// number of literal characters.
+fn specificity(pattern: &str) -> (bool, usize) {
+    let literal = pattern.chars().filter(|c| !matches!(c, '*' | '?')).count();
+    (literal == pattern.len(), literal)
+}
+
+/// The rule from `policy` that applies to `tool`, if any.
+pub fn resolve<'a>(
+    policy: &'a BTreeMap<String, ToolAction>,
+    tool: &str,
+) -> Option<(&'a str, ToolAction)> {
+    policy
+        .iter()
+        .filter(|(pattern, _)| glob_match(strip_server_prefix(pattern), tool))
+        .max_by_key(|(pattern, _)| specificity(strip_server_prefix(pattern)))
+        .map(|(pattern, action)| (pattern.as_str(), *action))
+}
+
+/// Every catalogue tool with its effective policy.
+pub fn policy_entries(policy: &BTreeMap<String, ToolAction>) -> Vec<ToolPolicyEntry> {
+    STUDIO_TOOLS
+        .iter()
+        .map(|tool| {
+            let matched = resolve(policy, tool.name);
+            ToolPolicyEntry {
+                name: tool.name,
+                mutates: tool.mutates,
+                action: matched.map(|(_, action)| action),
+                matched_by: matched.map(|(pattern, _)| pattern.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// OpenCode's top-level `permission` map for `policy`: every glob as
+/// written (OpenCode matches wildcards itself), plus one explicit entry
+/// per affected catalogue tool.
+pub fn permission_config(
+    policy: &BTreeMap<String, ToolAction>,
+) -> BTreeMap<String, PermissionRule> {
+    let globs = policy
+        .iter()
+        .filter(|(pattern, _)| pattern.contains(['*', '?']))
+        .map(|(pattern, action)| (opencode_tool_id(strip_server_prefix(pattern)), *action));
+    let tools = policy_entries(policy)
+        .into_iter()
+        .filter_map(|entry| Some((opencode_tool_id(entry.name), entry.action?)));
+    globs
+        .chain(tools)
+        .map(|(id, action)| (id, action.into()))
+        .collect()
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+/// All known roblox-studio tools and the policy currently applied to each.
+#[tauri::command]
+pub fn list_studio_tools() -> Vec<ToolPolicyEntry> {
+    policy_entries(&crate::config::get().tool_policy)
+}
+
+/// Set the action for a tool name or glob, or remove the rule when
+/// `action` is `None`. Rules are stored by bare tool name, so
+/// `roblox-studio_execute_luau` and `execute_luau` are the same rule.
+/// Applied to the running server right away.
+#[tauri::command]
+pub async fn set_tool_policy(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+    pattern: String,
+    action: Option<ToolAction>,
+) -> Result<Vec<ToolPolicyEntry>, String> {
+    let bare = strip_server_prefix(&pattern).to_string();
+    let cfg = crate::config::update(&app, |cfg| {
+        // Drop the prefixed spelling too, should an older config have it.
+        cfg.tool_policy.remove(&opencode_tool_id(&bare));
+        match action {
+            Some(action) => {
+                cfg.tool_policy.insert(bare, action);
+            }
+            None => {
+                cfg.tool_policy.remove(&bare);
+            }
+        }
+        Ok(())
+    })?;
+    match crate::opencode::reconfigure(state.inner(), &app).await {
+        Ok(outcome) => log::info!("Tool policy applied ({outcome:?})"),
+        Err(e) => log::warn!("Tool policy saved but not applied yet: {e}"),
+    }
+    Ok(policy_entries(&cfg.tool_policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(rules: &[(&str, ToolAction)]) -> BTreeMap<String, ToolAction> {
+        rules
+            .iter()
+            .map(|(pattern, action)| (pattern.to_string(), *action))
+            .collect()
+    }
+
+    #[test]
+    fn glob_match_handles_star_and_question_mark() {
+        assert!(glob_match("set_property", "set_property"));
+        assert!(!glob_match("set_property", "set_properties"));
+        assert!(glob_match("*", "execute_luau"));
+        assert!(glob_match("set_*", "set_property"));
+        assert!(glob_match("*_script_*", "set_script_source"));
+        assert!(!glob_match("set_*", "mass_set_property"));
+        assert!(glob_match("get_tag?", "get_tags"));
+        assert!(!glob_match("get_tag?", "get_tagged"));
+        assert!(!glob_match("get_tags?", "get_tags"));
+        assert!(glob_match("*a*a*", "mass_duplicate"));
+    }
+
+    #[test]
+    fn exact_name_beats_any_glob() {
+        let rules = policy(&[
+            ("*", ToolAction::Deny),
+            ("execute_*", ToolAction::Ask),
+            ("execute_luau", ToolAction::Allow),
+        ]);
+        assert_eq!(
+            resolve(&rules, "execute_luau"),
+            Some(("execute_luau", ToolAction::Allow))
+        );
+        assert_eq!(resolve(&rules, "add_tag"), Some(("*", ToolAction::Deny)));
+    }
+
+    #[test]
+    fn longer_literal_glob_wins() {
+        let rules = policy(&[
+            ("set_*", ToolAction::Ask),
+            ("set_script_*", ToolAction::Allow),
+        ]);
+        assert_eq!(
+            resolve(&rules, "set_script_source"),
+            Some(("set_script_*", ToolAction::Allow))
+        );
+        assert_eq!(
+            resolve(&rules, "set_property"),
+            Some(("set_*", ToolAction::Ask))
+        );
+        assert_eq!(resolve(&rules, "get_tags"), None);
+    }
+
+    #[test]
+    fn bare_and_prefixed_names_are_the_same_rule() {
+        let rules = policy(&[
+            ("roblox-studio_execute_luau", ToolAction::Deny),
+            ("roblox-studio_get_*", ToolAction::Allow),
+            ("get_tags", ToolAction::Ask),
+        ]);
+        assert_eq!(
+            resolve(&rules, "execute_luau"),
+            Some(("roblox-studio_execute_luau", ToolAction::Deny))
+        );
+        assert_eq!(
+            resolve(&rules, "get_attribute"),
+            Some(("roblox-studio_get_*", ToolAction::Allow))
+        );
+        assert_eq!(
+            resolve(&rules, "get_tags"),
+            Some(("get_tags", ToolAction::Ask))
+        );
+
+        assert!(validate_pattern("roblox-studio_execute_luau").is_ok());
+        assert!(validate_pattern("execute_luau").is_ok());
+        assert!(validate_pattern("roblox-studio_").is_err());
+        assert!(validate_pattern("Execute_Luau").is_err());
+    }
+
+    #[test]
+    fn permission_config_passes_globs_through() {
+        let rules = policy(&[
+            ("roblox-studio_mass_*", ToolAction::Ask),
+            ("get_*", ToolAction::Allow),
+            ("mass_get_property", ToolAction::Allow),
+        ]);
+        let permission = permission_config(&rules);
+
+        assert_eq!(
+            permission["roblox-studio_mass_*"],
+            PermissionRule::Action(ToolAction::Ask)
+        );
+        assert_eq!(
+            permission["roblox-studio_get_*"],
+            PermissionRule::Action(ToolAction::Allow)
+        );
+        assert_eq!(
+            permission["roblox-studio_mass_set_property"],
+            PermissionRule::Action(ToolAction::Ask)
+        );
+        assert_eq!(
+            permission["roblox-studio_mass_get_property"],
+            PermissionRule::Action(ToolAction::Allow)
+        );
+        assert!(!permission.contains_key("roblox-studio_execute_luau"));
+    }
+}
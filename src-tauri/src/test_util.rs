@@ -0,0 +1,66 @@
+//! Fixtures shared by the unit tests.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// An empty directory under the system temp dir, deleted again when
+/// dropped, so a failing test does not leave it behind.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `test` names the directory, to tell leftovers of a killed run apart.
+    pub fn new(test: &str) -> Self {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "bloxbot-{test}-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Points `BLOXBOT_HOME` at a test root and replaces the in-memory config,
+/// restoring both when dropped so later tests see what they saw before.
+// Only the Unix-only Rojo test uses it so far.
+#[cfg_attr(not(unix), allow(dead_code))]
+pub struct GlobalConfig {
+    home: Option<OsString>,
+    cfg: crate::config::AppConfig,
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+impl GlobalConfig {
+    pub fn set(root: &Path, cfg: crate::config::AppConfig) -> Self {
+        let guard = Self {
+            home: std::env::var_os(crate::paths::WORKSPACE_ROOT_ENV),
+            cfg: crate::config::get(),
+        };
+        std::env::set_var(crate::paths::WORKSPACE_ROOT_ENV, root);
+        crate::config::set(cfg);
+        guard
+    }
+}
+
+impl Drop for GlobalConfig {
+    fn drop(&mut self) {
+        match &self.home {
+            Some(home) => std::env::set_var(crate::paths::WORKSPACE_ROOT_ENV, home),
+            None => std::env::remove_var(crate::paths::WORKSPACE_ROOT_ENV),
+        }
+        crate::config::set(self.cfg.clone());
+    }
+}
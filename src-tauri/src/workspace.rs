@@ -0,0 +1,411 @@
+//! Moving the workspace root.
+//!
+//! The root (see `paths::workspace_root`) holds the default project, the
+//! managed projects and every project's `.opencode` data, cache and state.
+//! `migrate_workspace` moves all of it to a new location: the sidecar and
+//! the file watchers are stopped so nothing touches the tree mid-copy, the
+//! tree is copied and compared file by file, and only then is
+//! `AppConfig::workspace_root` switched. The old tree is deleted once
+//! OpenCode runs at the new location. Any failure before the switch leaves
+//! the old workspace in place and removes the partial copy.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::opencode::SharedOpenCodeState;
+
+/// What `migrate_workspace` did.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    pub files: u64,
+    pub bytes: u64,
+    /// `false` if the copy succeeded but the old tree could not be fully
+    /// deleted; it is safe to remove by hand.
+    pub old_removed: bool,
+}
+
+// ── Copy and verify ─────────────────────────────────────────────────────
+
+#[derive(Debug, Default)]
+struct CopyStats {
+    files: u64,
+    bytes: u64,
+}
+
+/// The root being copied and where it is copied to.
+#[derive(Debug, Clone, Copy)]
+struct Roots<'a> {
+    from: &'a Path,
+    to: &'a Path,
+}
+
+/// Recursively copy `from` into `to`. Symlinks are recreated, not followed.
+fn copy_tree(from: &Path, to: &Path, roots: Roots, stats: &mut CopyStats) -> Result<(), String> {
+    std::fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {e}", to.display()))?;
+    let entries =
+        std::fs::read_dir(from).map_err(|e| format!("Failed to read {}: {e}", from.display()))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {e}", from.display()))?;
+        let src = entry.path();
+        let dst = to.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .map_err(|e| format!("Failed to stat {}: {e}", src.display()))?;
+        if file_type.is_dir() {
+            copy_tree(&src, &dst, roots, stats)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&src, &dst, roots)?;
+        } else {
+            stats.bytes += std::fs::copy(&src, &dst)
+                .map_err(|e| format!("Failed to copy {}: {e}", src.display()))?;
+            stats.files += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Absolute links into the copied root (like the projects' shared
+/// `auth.json`) are pointed at the same file in the copy, since the
+/// original is deleted afterwards.
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path, roots: Roots) -> Result<(), String> {
+    let target =
+        std::fs::read_link(src).map_err(|e| format!("Failed to read {}: {e}", src.display()))?;
+    let target = match target.strip_prefix(roots.from) {
+        Ok(rest) => roots.to.join(rest),
+        Err(_) => target,
+    };
+    std::os::unix::fs::symlink(&target, dst)
+        .map_err(|e| format!("Failed to create {}: {e}", dst.display()))
+}
+
+#[cfg(windows)]
+fn copy_symlink(src: &Path, dst: &Path, roots: Roots) -> Result<(), String> {
+    // Creating symlinks needs extra privileges on Windows; copy what the
+    // link points to instead.
+    if src.is_dir() {
+        copy_tree(src, dst, roots, &mut CopyStats::default())
+    } else {
+        std::fs::copy(src, dst)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy {}: {e}", src.display()))
+    }
+}
+
+fn same_contents(a: &Path, b: &Path) -> Result<bool, String> {
+    let len = |p: &Path| {
+        std::fs::metadata(p)
+            .map(|m| m.len())
+            .map_err(|e| format!("Failed to stat {}: {e}", p.display()))
+    };
+    if len(a)? != len(b)? {
+        return Ok(false);
+    }
+    let open = |p: &Path| File::open(p).map_err(|e| format!("Failed to open {}: {e}", p.display()));
+    let (mut fa, mut fb) = (open(a)?, open(b)?);
+    let (mut ba, mut bb) = (vec![0u8; 64 * 1024], vec![0u8; 64 * 1024]);
+    loop {
+        let n = fa
+            .read(&mut ba)
+            .map_err(|e| format!("Failed to read {}: {e}", a.display()))?;
+        if n == 0 {
+            return Ok(true);
+        }
+        fb.read_exact(&mut bb[..n])
+            .map_err(|e| format!("Failed to read {}: {e}", b.display()))?;
+        if ba[..n] != bb[..n] {
+            return Ok(false);
+        }
+    }
+}
+
+/// Check that every regular file under `from` exists under `to` with the
+/// same contents.
+fn verify_tree(from: &Path, to: &Path) -> Result<(), String> {
+    let entries =
+        std::fs::read_dir(from).map_err(|e| format!("Failed to read {}: {e}", from.display()))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {e}", from.display()))?;
+        let src = entry.path();
+        let dst = to.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .map_err(|e| format!("Failed to stat {}: {e}", src.display()))?;
+        if file_type.is_dir() {
+            verify_tree(&src, &dst)?;
+        } else if file_type.is_file() && !same_contents(&src, &dst)? {
+            return Err(format!("{} differs from {}", dst.display(), src.display()));
+        }
+    }
+    Ok(())
+}
+
+/// `path` with symlinks and `..` resolved. Only its longest existing
+/// ancestor has to exist; the rest is appended as given.
+fn resolve(path: &Path) -> Result<PathBuf, String> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => return Err(format!("{} has no existing parent", path.display())),
+        }
+    }
+    let resolved = std::fs::canonicalize(existing)
+        .map_err(|e| format!("Failed to resolve {}: {e}", existing.display()))?;
+    Ok(rest.iter().rev().fold(resolved, |p, name| p.join(name)))
+}
+
+/// Reject targets that would nest the workspace inside itself or
+/// overwrite existing data.
+fn check_target(from: &Path, to: &Path) -> Result<(), String> {
+    if !to.is_absolute() {
+        return Err(format!("{} is not an absolute path", to.display()));
+    }
+    let (from_resolved, to_resolved) = (resolve(from)?, resolve(to)?);
+    if to_resolved.starts_with(&from_resolved) || from_resolved.starts_with(&to_resolved) {
+        return Err(format!(
+            "{} and {} overlap; pick a separate folder",
+            to.display(),
+            from.display()
+        ));
+    }
+    if to.exists()
+        && std::fs::read_dir(to)
+            .map_err(|e| format!("Failed to read {}: {e}", to.display()))?
+            .next()
+            .is_some()
+    {
+        return Err(format!("{} already exists and is not empty", to.display()));
+    }
+    Ok(())
+}
+
+/// Keeps the instruction watcher paused for as long as it lives.
+struct InstructionWatcherPaused;
+
+impl InstructionWatcherPaused {
+    fn new() -> Self {
+        crate::instructions::pause_watcher(true);
+        Self
+    }
+}
+
+impl Drop for InstructionWatcherPaused {
+    fn drop(&mut self) {
+        crate::instructions::pause_watcher(false);
+    }
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+/// Move the workspace root, with all projects and OpenCode data, to
+/// `new_root` and restart OpenCode there.
+#[tauri::command]
+pub async fn migrate_workspace(
+    state: tauri::State<'_, SharedOpenCodeState>,
+    app: AppHandle,
+    new_root: String,
+) -> Result<MigrationReport, String> {
+    if crate::paths::workspace_root_from_env().is_some() {
+        return Err(format!(
+            "The workspace location is set by {}; change or unset it instead",
+            crate::paths::WORKSPACE_ROOT_ENV
+        ));
+    }
+    let from = crate::paths::workspace_root()?;
+    let to = PathBuf::from(new_root);
+    check_target(&from, &to)?;
+
+    log::info!(
+        "Migrating workspace from {} to {}",
+        from.display(),
+        to.display()
+    );
+    let _paused = InstructionWatcherPaused::new();
+    let synced = crate::script_sync::pause(&app).await;
+    crate::opencode::stop_all(state.inner(), &app).await;
+
+    let copy = {
+        let (from, to) = (from.clone(), to.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut stats = CopyStats::default();
+            let roots = Roots {
+                from: &from,
+                to: &to,
+            };
+            copy_tree(&from, &to, roots, &mut stats)?;
+            verify_tree(&from, &to)?;
+            Ok::<_, String>(stats)
+        })
+        .await
+        .map_err(|e| format!("Workspace copy task failed: {e}"))
+        .and_then(|result| result)
+    };
+
+    let switched = copy.and_then(|stats| {
+        crate::config::update(&app, |cfg| {
+            cfg.workspace_root = Some(to.clone());
+            // Projects kept at an explicit path inside the old root moved
+            // along with it.
+            for project in &mut cfg.projects {
+                if let Some(path) = &project.path {
+                    if let Ok(rel) = path.strip_prefix(&from) {
+                        project.path = Some(to.join(rel));
+                    }
+                }
+            }
+            Ok(())
+        })
+        .map(|_| stats)
+    });
+
+    let stats = match switched {
+        Ok(stats) => stats,
+        Err(e) => {
+            log::error!("Workspace migration failed: {e}");
+            if to.exists() {
+                if let Err(e) = std::fs::remove_dir_all(&to) {
+                    log::warn!("Failed to remove partial copy {}: {e}", to.display());
+                }
+            }
+            if let Err(e) = crate::opencode::restart(state.inner(), &app).await {
+                log::warn!("OpenCode did not restart after the failed migration: {e}");
+            }
+            if let Some(workspace) = synced {
+                crate::script_sync::resume(&app, workspace).await;
+            }
+            return Err(e);
+        }
+    };
+    log::info!(
+        "Copied and verified {} files ({} bytes)",
+        stats.files,
+        stats.bytes
+    );
+
+    // Keep the old tree until OpenCode is up at the new location.
+    crate::opencode::restart(state.inner(), &app)
+        .await
+        .map_err(|e| {
+            format!(
+                "Moved the workspace to {}, but OpenCode did not start there: {e}. The old workspace at {} was kept.",
+                to.display(),
+                from.display()
+            )
+        })?;
+    if let Some(workspace) = synced {
+        let moved = match workspace.strip_prefix(&from) {
+            Ok(rel) => to.join(rel),
+            Err(_) => workspace,
+        };
+        crate::script_sync::resume(&app, moved).await;
+    }
+
+    let old_removed = match std::fs::remove_dir_all(&from) {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("Failed to remove old workspace {}: {e}", from.display());
+            false
+        }
+    };
+
+    Ok(MigrationReport {
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+        files: stats.files,
+        bytes: stats.bytes,
+        old_removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn check_target_resolves_links_and_dot_dot() {
+        let temp = TempDir::new("workspace-target");
+        let base = temp.path();
+        let from = base.join("root");
+        std::fs::create_dir_all(from.join("projects")).unwrap();
+
+        // Inside the root, spelled so a plain prefix check misses it.
+        let dotted = from.join("projects").join("..").join("new");
+        let err = check_target(&from, &dotted).unwrap_err();
+        assert!(err.contains("overlap"), "{err}");
+        #[cfg(unix)]
+        {
+            let link = base.join("link");
+            std::os::unix::fs::symlink(&from, &link).unwrap();
+            let err = check_target(&from, &link.join("new")).unwrap_err();
+            assert!(err.contains("overlap"), "{err}");
+        }
+        assert!(check_target(&from, &base.join("moved")).is_ok());
+    }
+
+    #[test]
+    fn copied_tree_verifies_until_a_file_changes() {
+        let temp = TempDir::new("workspace-copy");
+        let (from, to) = (temp.path().join("from"), temp.path().join("to"));
+        let data = from.join(".opencode").join("data").join("opencode");
+        std::fs::create_dir_all(&data).unwrap();
+        std::fs::write(data.join("auth.json"), "{}").unwrap();
+        std::fs::create_dir_all(from.join("default").join("src")).unwrap();
+        std::fs::write(
+            from.join("default").join("src").join("main.luau"),
+            "print(1)",
+        )
+        .unwrap();
+        #[cfg(unix)]
+        let project_data = {
+            let project_data = from
+                .join("projects")
+                .join("obby")
+                .join(".opencode")
+                .join("data")
+                .join("opencode");
+            std::fs::create_dir_all(&project_data).unwrap();
+            std::os::unix::fs::symlink(data.join("auth.json"), project_data.join("auth.json"))
+                .unwrap();
+            std::os::unix::fs::symlink("../src", from.join("default").join("link")).unwrap();
+            project_data
+        };
+
+        let mut stats = CopyStats::default();
+        let roots = Roots {
+            from: &from,
+            to: &to,
+        };
+        copy_tree(&from, &to, roots, &mut stats).unwrap();
+        verify_tree(&from, &to).unwrap();
+        assert_eq!(stats.files, 2);
+        #[cfg(unix)]
+        {
+            let rel = project_data.strip_prefix(&from).unwrap().join("auth.json");
+            assert_eq!(
+                std::fs::read_link(to.join(rel)).unwrap(),
+                to.join(".opencode/data/opencode/auth.json")
+            );
+            assert_eq!(
+                std::fs::read_link(to.join("default").join("link")).unwrap(),
+                Path::new("../src")
+            );
+        }
+
+        std::fs::write(to.join("default").join("src").join("main.luau"), "print(2)").unwrap();
+        let err = verify_tree(&from, &to).unwrap_err();
+        assert!(err.contains("main.luau"), "{err}");
+    }
+}
@@ -1,4 +1,6 @@
+import { listen } from "@tauri-apps/api/event";
 import { useEffect, useState } from "react";
+import { toast } from "sonner";
 
 import Chat from "@/components/Chat";
 import ConnectionBanner from "@/components/ConnectionBanner";
@@ -11,6 +13,7 @@ import { Toaster } from "@/components/ui/sonner";
 import { useUpdater } from "@/hooks/useUpdater";
 import { capture } from "@/lib/telemetry";
 import { useStore } from "@/stores/opencode";
+import type { SecondInstancePayload } from "@/types";
 
 function App() {
   const { status, pendingUpdate, installUpdate, dismissUpdate } = useUpdater();
@@ -21,6 +24,22 @@ function App() {
     capture("app_launched");
   }, []);
 
+  // A second launch focuses this window instead of starting its own server.
+  useEffect(() => {
+    const unlisten = listen<SecondInstancePayload>("second-instance", (event) => {
+      const { args, cwd } = event.payload;
+      console.debug(
+        "frontend",
+        `Second launch redirected here (args ${JSON.stringify(args)}, cwd ${cwd})`,
+      );
+      toast.info("BloxBot is already running");
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   return (
     <OpenCodeProvider>
       <main className="flex h-full flex-col overflow-hidden">
@@ -42,7 +42,9 @@ function Chat() {
   const handleSessionSelect = useCallback(() => setShowSettings(false), []);
   const handleOpenSettings = useCallback(() => setShowSettings(true), []);
 
-  const serverRunning = status === "Running";
+  // A degraded server is still up; keep the chat mounted while the
+  // backend watchdog decides whether to restart it.
+  const serverRunning = status === "Running" || status === "Degraded";
   const isError = typeof status === "object" && "Error" in status;
 
   async function handleRetry() {
@@ -1,6 +1,7 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
+import type { OpenCodeMetrics, ProcessMetrics } from "@/types";
 
 // ── Types ───────────────────────────────────────────────────────────────
 
@@ -51,6 +52,23 @@ function formatTimestamp(epochMs: number): string {
   return new Date(epochMs).toISOString().slice(11, 23);
 }
 
+const ROLE_LABELS: Record<ProcessMetrics["role"], string> = {
+  Sidecar: "opencode",
+  Launcher: "launcher",
+  McpServer: "mcp",
+};
+
+function formatBytes(bytes: number): string {
+  const mb = bytes / (1024 * 1024);
+  return mb >= 1024 ? `${(mb / 1024).toFixed(1)} GB` : `${mb.toFixed(0)} MB`;
+}
+
+function formatUptime(secs: number): string {
+  const h = Math.floor(secs / 3600);
+  const m = Math.floor((secs % 3600) / 60);
+  return h > 0 ? `${h}h${m}m` : `${m}m${secs % 60}s`;
+}
+
 const MAX_ENTRIES = 5000;
 /** How often (ms) to flush batched log entries into React state. */
 const FLUSH_INTERVAL_MS = 100;
@@ -62,6 +80,7 @@ function DebugLogs() {
   const [filter, setFilter] = useState<LogLevel | null>(null);
   const [search, setSearch] = useState("");
   const [autoScroll, setAutoScroll] = useState(true);
+  const [metrics, setMetrics] = useState<OpenCodeMetrics | null>(null);
   const scrollRef = useRef<HTMLDivElement>(null);
   const idRef = useRef(0);
 
@@ -121,6 +140,23 @@ function DebugLogs() {
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, []);
 
+  // Resource metrics: initial snapshot, then periodic pushes from Rust.
+  useEffect(() => {
+    let cancelled = false;
+    const unlistenPromise = listen<OpenCodeMetrics>("opencode-metrics", (event) => {
+      if (!cancelled) setMetrics(event.payload);
+    });
+    invoke<OpenCodeMetrics>("get_opencode_metrics")
+      .then((m) => {
+        if (!cancelled) setMetrics(m);
+      })
+      .catch((err) => console.error("Failed to load metrics:", err));
+    return () => {
+      cancelled = true;
+      unlistenPromise.then((fn) => fn());
+    };
+  }, []);
+
   // Auto-scroll via rAF — avoids synchronous layout thrash on every state update.
   // biome-ignore lint/correctness/useExhaustiveDependencies: logs used as trigger
   useEffect(() => {
@@ -268,6 +304,17 @@ function DebugLogs() {
             ? `${logs.length} entries`
             : `${filtered.length} / ${logs.length} entries`}
         </span>
+        {metrics && metrics.processes.length > 0 && (
+          <span className="flex items-center gap-3">
+            {metrics.processes.map((p) => (
+              <span key={p.pid} title={`PID ${p.pid}`}>
+                {ROLE_LABELS[p.role]} {p.cpuPercent.toFixed(0)}% {formatBytes(p.rssBytes)}
+                {p.openFiles !== null && ` ${p.openFiles} fd`} {formatUptime(p.uptimeSecs)}
+              </span>
+            ))}
+            <span>restarts {metrics.restartCount}</span>
+          </span>
+        )}
         {!autoScroll && (
           <button
             type="button"
@@ -6,7 +6,7 @@ import { useEffect, useRef } from "react";
 import { toast } from "sonner";
 
 import { useStore } from "@/stores/opencode";
-import type { OpenCodeStatus } from "@/types";
+import type { OpenCodeStatus, StatusPayload } from "@/types";
 
 const SSE_RECONNECT_DELAY = 3000;
 /** After this many consecutive SSE failures, show a reconnect toast. */
@@ -15,14 +15,9 @@ const SSE_FAILURE_THRESHOLD = 3;
 const STUDIO_POLL_FAST = 500;
 const STUDIO_POLL_SLOW = 5000;
 
-interface StatusPayload {
-  status: OpenCodeStatus;
-  port: number;
-}
-
 function serverStatusLabel(status: OpenCodeStatus): "starting" | "running" | "stopped" | "error" {
-  if (status === "Running") return "running";
-  if (status === "Starting") return "starting";
+  if (status === "Running" || status === "Degraded") return "running";
+  if (status === "Starting" || status === "Restarting") return "starting";
   if (typeof status === "object" && "Error" in status) return "error";
   return "stopped";
 }
@@ -93,7 +88,7 @@ function OpenCodeProvider({ children }: OpenCodeProviderProps) {
         cancelled = true;
         clearTimeout(retryTimer);
       };
-    } else if (status !== "Running" && client) {
+    } else if (status !== "Running" && status !== "Degraded" && client) {
       console.debug("frontend", "Server no longer running, clearing client");
       useStore.getState().setClient(null);
     }
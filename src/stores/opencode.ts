@@ -705,6 +705,17 @@ export const useStore = create<OpenCodeState>((set, get) => {
           opts.variant = selectedVariant;
         }
 
+        // Snapshot the workspace so this turn can be reverted. The checkpoint
+        // picks the message ID, so it is keyed by the message it precedes.
+        try {
+          const checkpoint = await invoke<{ messageId: string }>("create_checkpoint", {
+            sessionId: activeSession.id,
+          });
+          opts.messageID = checkpoint.messageId;
+        } catch (err) {
+          console.warn("Failed to create checkpoint:", err);
+        }
+
         await c.session.promptAsync(opts as Parameters<typeof c.session.promptAsync>[0]);
         capture("message_sent", {
           model: selectedModel ?? undefined,
@@ -943,6 +954,9 @@ export const useStore = create<OpenCodeState>((set, get) => {
         case "session.deleted": {
           const session = event.properties.info;
           if (session) {
+            invoke("delete_checkpoints", { sessionId: session.id }).catch((err) =>
+              console.warn("Failed to delete checkpoints:", err),
+            );
             set((state) => ({
               allSessions: state.allSessions.filter((s) => s.id !== session.id),
               activeSession: session.id === currentSessionId ? null : state.activeSession,
@@ -3,7 +3,72 @@ import type { Message, Part, ProviderAuthMethod } from "@opencode-ai/sdk/v2/clie
 // ── OpenCode sidecar status (from Rust backend) ─────────────────────────
 
 /** Status of the OpenCode sidecar process (from Rust backend). */
-export type OpenCodeStatus = "Stopped" | "Starting" | "Running" | { Error: string };
+export type OpenCodeStatus =
+  | "Stopped"
+  | "Starting"
+  | "Running"
+  | "Degraded"
+  | "Restarting"
+  | { Error: string };
+
+/** Step the backend is executing while the server starts. */
+export type StartupPhase =
+  | "ResolvingNode"
+  | "CleaningStaleProcesses"
+  | "AllocatingPorts"
+  | "WritingConfig"
+  | "Spawning"
+  | "WaitingForHealth"
+  | "McpConnecting";
+
+/** Machine-readable cause of a startup failure. */
+export type StartupErrorCode =
+  | "NodeMissing"
+  | "LauncherMissing"
+  | "PortExhausted"
+  | "PortTaken"
+  | "ConfigWriteFailed"
+  | "SpawnFailed"
+  | "HealthTimeout"
+  | "EarlyExit"
+  | "TooManyCrashes"
+  | "Internal";
+
+/** Payload of the `opencode-status-changed` event. */
+export interface StatusPayload {
+  status: OpenCodeStatus;
+  port: number;
+  /** Automatic restarts attempted since the server was last stable. */
+  restartCount: number;
+  maxRestarts: number;
+  lastExitReason: string | null;
+  phase: StartupPhase | null;
+  errorCode: StartupErrorCode | null;
+}
+
+/** Payload of the `second-instance` event: a launch redirected to this instance. */
+export interface SecondInstancePayload {
+  args: string[];
+  cwd: string;
+}
+
+/** One process of the OpenCode tree, as sampled by `get_opencode_metrics`. */
+export interface ProcessMetrics {
+  role: "Sidecar" | "Launcher" | "McpServer";
+  pid: number;
+  cpuPercent: number;
+  rssBytes: number;
+  openFiles: number | null;
+  uptimeSecs: number;
+}
+
+/** Result of `get_opencode_metrics` and payload of `opencode-metrics`. */
+export interface OpenCodeMetrics {
+  processes: ProcessMetrics[];
+  restartCount: number;
+  healthyForSecs: number | null;
+  timestamp: number;
+}
 
 // ── Chat types ──────────────────────────────────────────────────────────
 